@@ -1,6 +1,12 @@
 //! Tauri commands exposed to the frontend.
 
-use tauri::{AppHandle, Manager, WebviewWindow};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, DragDropEvent, Manager, WebviewWindow, WindowEvent};
 use serde::{Deserialize, Serialize};
 
 use crate::platform;
@@ -21,31 +27,783 @@ pub fn get_platform() -> PlatformInfo {
     }
 }
 
+/// Everything a bug report needs in one JSON object. Never fails: a field
+/// whose lookup errors becomes `null` and its error is reported under
+/// `errors`, keyed by field name.
+#[tauri::command]
+pub fn get_diagnostics(app: AppHandle) -> serde_json::Value {
+    type Errors = serde_json::Map<String, serde_json::Value>;
+    fn field<T: Serialize>(errors: &mut Errors, name: &str, value: Result<T, platform::PlatformError>) -> serde_json::Value {
+        match value {
+            Ok(v) => serde_json::to_value(v).unwrap_or(serde_json::Value::Null),
+            Err(e) => {
+                errors.insert(name.to_string(), e.to_string().into());
+                serde_json::Value::Null
+            }
+        }
+    }
+    let env = |key: &str| std::env::var(key).ok();
+    let display_server = if platform::is_wayland() {
+        "wayland"
+    } else if env("DISPLAY").is_some() {
+        "x11"
+    } else {
+        "unknown"
+    };
+
+    let mut errors = Errors::new();
+    let distro = field(&mut errors, "distro", platform::distro_name());
+    let kernel = field(&mut errors, "kernel", platform::kernel_version());
+    let memory = field(&mut errors, "memory_total_bytes", platform::total_memory_bytes());
+    let cpu_count = field(
+        &mut errors,
+        "cpu_count",
+        std::thread::available_parallelism().map(|n| n.get()).map_err(platform::PlatformError::Io),
+    );
+
+    serde_json::json!({
+        "platform": get_platform(),
+        "app_version": app.package_info().version.to_string(),
+        "distro": distro,
+        "kernel": kernel,
+        "desktop": env("XDG_CURRENT_DESKTOP"),
+        "session_type": env("XDG_SESSION_TYPE"),
+        "display_server": display_server,
+        "sandbox": platform::sandbox_kind(),
+        "memory_total_bytes": memory,
+        "cpu_count": cpu_count,
+        "errors": errors,
+    })
+}
+
+/// Captured once at startup for `get_app_uptime`.
+pub struct AppStart {
+    instant: std::time::Instant,
+    wall: std::time::SystemTime,
+}
+
+impl AppStart {
+    pub fn now() -> Self {
+        Self {
+            instant: std::time::Instant::now(),
+            wall: std::time::SystemTime::now(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AppUptime {
+    /// Unix epoch milliseconds when the app started
+    pub started_at_ms: u64,
+    pub uptime_ms: u64,
+}
+
+#[tauri::command]
+pub fn get_app_uptime(start: tauri::State<'_, AppStart>) -> AppUptime {
+    let started_at_ms = start
+        .wall
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    AppUptime {
+        started_at_ms,
+        uptime_ms: start.instant.elapsed().as_millis() as u64,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LaunchArgs {
+    /// Full argv, including the program path
+    pub args: Vec<String>,
+    /// First argument that looks like a custom-scheme link (e.g. "quack://open?x=1")
+    pub deep_link: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_launch_args() -> LaunchArgs {
+    let args = platform::launch_args().to_vec();
+    let deep_link = args
+        .iter()
+        .skip(1)
+        .find(|arg| {
+            tauri::Url::parse(arg)
+                .map(|url| url.scheme().len() > 1 && !matches!(url.scheme(), "file" | "http" | "https"))
+                .unwrap_or(false)
+        })
+        .cloned();
+    LaunchArgs { args, deep_link }
+}
+
+/// How the app was asked to start; managed so the frontend can tell it was
+/// launched hidden (e.g. from autostart) and stay out of the way.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StartupOptions {
+    /// The main window is created hidden (`--hidden`)
+    pub hidden: bool,
+    /// argv after the program path
+    pub args: Vec<String>,
+    /// Log every shell command to this audit log
+    #[serde(skip)]
+    pub audit: Option<platform::CommandAudit>,
+    /// Extra redaction patterns, kept so `with_audit_redact` works in
+    /// either order with `with_command_audit`
+    #[serde(skip)]
+    audit_redact: Vec<String>,
+}
+
+impl StartupOptions {
+    pub fn from_args() -> Self {
+        StartupOptions {
+            hidden: false,
+            args: platform::launch_args().iter().skip(1).cloned().collect(),
+            audit: None,
+            audit_redact: Vec::new(),
+        }
+    }
+
+    pub fn with_startup_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Append every command started through `run`, `spawn` and the other
+    /// shell helpers to `path` as JSON lines, with secrets in the
+    /// environment redacted: variables matching
+    /// `platform::DEFAULT_REDACT_PATTERNS` (plus any `with_audit_redact`).
+    pub fn with_command_audit(mut self, path: PathBuf) -> Self {
+        self.audit = Some(platform::CommandAudit::new(path, self.audit_redact.clone()));
+        self
+    }
+
+    /// Also redact variables matching these globs (e.g. `"*_KEY"`,
+    /// case-insensitive) from the command audit log.
+    pub fn with_audit_redact(mut self, patterns: Vec<String>) -> Self {
+        if let Some(audit) = &mut self.audit {
+            audit.redact.extend(patterns.iter().cloned());
+        }
+        self.audit_redact.extend(patterns);
+        self
+    }
+
+    /// Turn on the command audit if configured, and show the main window
+    /// unless starting hidden. The window is declared with `visible: false`
+    /// so it never flashes up before this runs.
+    pub fn apply(&self, app: &AppHandle) -> Result<(), String> {
+        if let Some(audit) = &self.audit {
+            platform::set_command_audit(audit.clone());
+        }
+        if self.hidden {
+            return Ok(());
+        }
+        let w = get_window(app, None)?;
+        w.show().map_err(|e| e.to_string())?;
+        w.set_focus().map_err(|e| e.to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_startup_args(startup: tauri::State<'_, StartupOptions>) -> StartupOptions {
+    startup.inner().clone()
+}
+
+/// The command audit log, or `null` when auditing is off.
+#[tauri::command]
+pub fn get_audit_log_path() -> Option<String> {
+    platform::audit_log_path().map(|p| p.display().to_string())
+}
+
+/// Cargo features compiled into this build, so the frontend can hide what
+/// isn't available (e.g. ["privileged"]).
+#[tauri::command]
+pub fn get_enabled_features() -> Vec<&'static str> {
+    let features = [("privileged", cfg!(feature = "privileged")), ("automation", cfg!(feature = "automation"))];
+    features.into_iter().filter(|&(_, enabled)| enabled).map(|(name, _)| name).collect()
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SpawnInput {
     /// Shell command to execute (e.g., "echo hello" or "ls -la")
     pub command: String,
+    /// Append stdout to this file (parent dirs are created). When set,
+    /// `RunOutput.stdout` is empty.
+    pub stdout_file: Option<String>,
+    /// Append stderr to this file (parent dirs are created). When set,
+    /// `RunOutput.stderr` is empty.
+    pub stderr_file: Option<String>,
+    /// Kill the command if it uses more than this much memory
+    pub mem_limit_mb: Option<u64>,
+    /// Working directory (must exist)
+    pub cwd: Option<String>,
+    /// Extra environment variables, added to the inherited environment
+    pub env: Option<HashMap<String, String>>,
+    /// Kill the command after this many milliseconds
+    pub timeout_ms: Option<u64>,
+    /// Error on non-UTF-8 output instead of replacing invalid bytes
+    pub strict_utf8: Option<bool>,
+    /// Regex with named groups applied to stderr on a non-zero exit,
+    /// e.g. `(?P<file>[^:]+):(?P<line>\d+):(?P<col>\d+): (?P<message>.*)`
+    pub stderr_pattern: Option<String>,
+}
+
+impl SpawnInput {
+    fn shell_options(&self) -> platform::ShellOptions {
+        platform::ShellOptions {
+            stdout_file: self.stdout_file.as_ref().map(PathBuf::from),
+            stderr_file: self.stderr_file.as_ref().map(PathBuf::from),
+            mem_limit_mb: self.mem_limit_mb,
+            cwd: self.cwd.as_ref().map(PathBuf::from),
+            env: self.env.clone().unwrap_or_default().into_iter().collect(),
+            timeout: self.timeout_ms.map(Duration::from_millis),
+            strict_utf8: self.strict_utf8.unwrap_or(false),
+        }
+    }
+
+    /// Compiled up front so an invalid pattern fails before anything runs.
+    fn stderr_regex(&self) -> Result<Option<regex::Regex>, String> {
+        self.stderr_pattern
+            .as_deref()
+            .map(platform::compile_pattern)
+            .transpose()
+            .map_err(|e| e.to_string())
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct RunOutput {
+    /// Exit code; -1 when killed by a signal, -2 when `timeout_ms` elapsed
     pub status: i32,
     pub stdout: String,
     pub stderr: String,
+    /// The command was killed for exceeding `mem_limit_mb`; null when the
+    /// limit was enforced with `RLIMIT_AS` and this can't be known
+    pub oom_killed: Option<bool>,
+    /// The command was killed for exceeding `timeout_ms`
+    pub timed_out: bool,
+    /// Named groups of `stderr_pattern` when the command failed and it matched
+    pub parsed_error: Option<HashMap<String, String>>,
+}
+
+impl RunOutput {
+    fn with_parsed_error(mut self, pattern: Option<&regex::Regex>) -> Self {
+        if let (Some(re), true) = (pattern, self.status != 0) {
+            self.parsed_error = platform::named_captures(re, &self.stderr);
+        }
+        self
+    }
+}
+
+impl From<platform::ShellOutput> for RunOutput {
+    fn from(out: platform::ShellOutput) -> Self {
+        RunOutput {
+            status: if out.timed_out { -2 } else { out.status },
+            stdout: out.stdout,
+            stderr: out.stderr,
+            oom_killed: out.oom_killed,
+            timed_out: out.timed_out,
+            parsed_error: None,
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_shells() -> Result<platform::ShellList, String> {
+    platform::list_shells().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn spawn(input: SpawnInput) -> Result<(), String> {
-    platform::spawn_shell(&input.command).map_err(|e| e.to_string())
+    platform::spawn_shell(&input.command, &input.shell_options()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn run(input: SpawnInput) -> Result<RunOutput, String> {
-    platform::run_shell_capture(&input.command)
-        .map(|(status, stdout, stderr)| RunOutput { status, stdout, stderr })
+    let pattern = input.stderr_regex()?;
+    platform::run_shell_capture(&input.command, &input.shell_options())
+        .map(|out| RunOutput::from(out).with_parsed_error(pattern.as_ref()))
+        .map_err(|e| e.to_string())
+}
+
+static NEXT_STREAM_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+#[derive(Debug, Deserialize)]
+pub struct RunStreamInput {
+    #[serde(flatten)]
+    pub spawn: SpawnInput,
+    /// Also append every output line to this file
+    pub tee_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamLine {
+    pub id: u64,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamExit {
+    pub id: u64,
+    /// -1 when the process was killed by a signal
+    pub status: i32,
+    /// Ended by `cancel_stream` or app shutdown
+    pub cancelled: bool,
+}
+
+struct RunningStream {
+    pid: u32,
+    cancelled: bool,
+    waiter: Option<std::thread::JoinHandle<()>>,
+}
+
+/// Streams started by `run_stream` that haven't exited yet, by stream id.
+#[derive(Default)]
+pub struct RunningStreams(Mutex<HashMap<u64, RunningStream>>);
+
+impl RunningStreams {
+    /// Kill every running stream and wait (briefly) for their reader
+    /// threads to drain and the exit events to go out.
+    pub fn shutdown(&self) {
+        let streams = match self.0.lock() {
+            Ok(mut map) => std::mem::take(&mut *map),
+            Err(_) => return,
+        };
+        for stream in streams.values() {
+            let _ = platform::kill_process_group(stream.pid);
+        }
+        let deadline = std::time::Instant::now() + Duration::from_secs(2);
+        for waiter in streams.into_values().filter_map(|s| s.waiter) {
+            while !waiter.is_finished() && std::time::Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            if waiter.is_finished() {
+                let _ = waiter.join();
+            }
+        }
+    }
+}
+
+/// Run a command without blocking and stream its output as `cmd://stdout`
+/// / `cmd://stderr` line events, followed by `cmd://exit`. Returns the
+/// stream id carried by every event. `cmd://exit` is sent however the
+/// process ends, including `cancel_stream` and app shutdown.
+#[tauri::command]
+pub fn run_stream(
+    app: AppHandle,
+    streams: tauri::State<'_, RunningStreams>,
+    input: RunStreamInput,
+) -> Result<u64, String> {
+    let id = NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed);
+    // Lock before spawning, so a poisoned registry fails the call without
+    // leaving an untracked process behind, and hold it until the entry is
+    // complete so the waiter can't try to remove it first.
+    let mut running = streams.0.lock().map_err(|e| e.to_string())?;
+    let emitter = app.clone();
+    let child = platform::spawn_shell_streaming(
+        &input.spawn.command,
+        &input.spawn.shell_options(),
+        input.tee_file.as_deref().map(std::path::Path::new),
+        move |stream, line| {
+            let channel = format!("cmd://{stream}");
+            platform::events::emit_per_id(&emitter, &channel, id, StreamLine { id, line: line.to_string() });
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    let pid = child.id();
+    let waiter = std::thread::spawn(move || {
+        let status = child.wait().unwrap_or(-1);
+        let cancelled = app
+            .state::<RunningStreams>()
+            .0
+            .lock()
+            .ok()
+            .and_then(|mut running| running.remove(&id))
+            .map_or(true, |s| s.cancelled);
+        platform::events::emit_unlimited(&app, "cmd://exit", StreamExit { id, status, cancelled });
+    });
+    running.insert(id, RunningStream { pid, cancelled: false, waiter: Some(waiter) });
+    Ok(id)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancelStreamInput {
+    pub id: u64,
+}
+
+/// Kill a `run_stream` command and everything it started. Returns false
+/// when the stream has already exited.
+#[tauri::command]
+pub fn cancel_stream(
+    streams: tauri::State<'_, RunningStreams>,
+    input: CancelStreamInput,
+) -> Result<bool, String> {
+    let mut running = streams.0.lock().map_err(|e| e.to_string())?;
+    let Some(stream) = running.get_mut(&input.id) else {
+        return Ok(false);
+    };
+    stream.cancelled = true;
+    platform::kill_process_group(stream.pid).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+static NEXT_SESSION_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Open `start_interactive` sessions, by session id.
+#[derive(Default)]
+pub struct InteractiveSessions(Mutex<HashMap<u64, platform::InteractiveChild>>);
+
+impl InteractiveSessions {
+    /// Close every session (EOF, then kill) and join their readers.
+    pub fn shutdown(&self) {
+        let sessions = match self.0.lock() {
+            Ok(mut map) => std::mem::take(&mut *map),
+            Err(_) => return,
+        };
+        for session in sessions.into_values() {
+            let _ = session.close(Duration::from_millis(200));
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartInteractiveInput {
+    #[serde(flatten)]
+    pub spawn: SpawnInput,
+    /// Run on a pseudo-terminal (for shells, REPLs, TUIs)
+    #[serde(default)]
+    pub pty: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InteractiveOutput {
+    pub session_id: u64,
+    /// "stdout" | "stderr" (always "stdout" with a pty)
+    pub stream: String,
+    pub data: String,
+}
+
+/// Start a command whose stdin is fed with `write_stdin`. Output arrives
+/// as `io://<session_id>/out` events and `io://<session_id>/end` fires once
+/// it has all been read. Sessions must be closed with `close_interactive`.
+#[tauri::command]
+pub fn start_interactive(
+    app: AppHandle,
+    sessions: tauri::State<'_, InteractiveSessions>,
+    input: StartInteractiveInput,
+) -> Result<u64, String> {
+    let session_id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+    let out_app = app.clone();
+    let out_channel = format!("io://{session_id}/out");
+    let child = platform::spawn_interactive(
+        &input.spawn.command,
+        &input.spawn.shell_options(),
+        input.pty,
+        move |stream, data| {
+            let payload = InteractiveOutput { session_id, stream: stream.to_string(), data: data.to_string() };
+            platform::events::emit(&out_app, &out_channel, payload);
+        },
+        move || platform::events::emit_unlimited(&app, &format!("io://{session_id}/end"), session_id),
+    )
+    .map_err(|e| e.to_string())?;
+    sessions.0.lock().map_err(|e| e.to_string())?.insert(session_id, child);
+    Ok(session_id)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WriteStdinInput {
+    pub session_id: u64,
+    pub data: String,
+}
+
+#[tauri::command]
+pub fn write_stdin(sessions: tauri::State<'_, InteractiveSessions>, input: WriteStdinInput) -> Result<(), String> {
+    let mut sessions = sessions.0.lock().map_err(|e| e.to_string())?;
+    let session = sessions
+        .get_mut(&input.session_id)
+        .ok_or_else(|| format!("no interactive session {}", input.session_id))?;
+    session.write(input.data.as_bytes()).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionInput {
+    pub session_id: u64,
+}
+
+/// Send EOF, then terminate the command if it hasn't exited within a
+/// second. Returns its exit code. The wait runs on a blocking worker, off
+/// both the main thread and the async runtime.
+#[tauri::command]
+pub async fn close_interactive(
+    sessions: tauri::State<'_, InteractiveSessions>,
+    input: SessionInput,
+) -> Result<i32, String> {
+    let session = sessions
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&input.session_id)
+        .ok_or_else(|| format!("no interactive session {}", input.session_id))?;
+    tauri::async_runtime::spawn_blocking(move || session.close(Duration::from_secs(1)))
+        .await
+        .map_err(|e| e.to_string())?
         .map_err(|e| e.to_string())
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RunDeadlineInput {
+    #[serde(flatten)]
+    pub spawn: SpawnInput,
+    /// Total wall-clock budget across all attempts
+    pub deadline_ms: u64,
+    pub max_attempts: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunDeadlineOutput {
+    #[serde(flatten)]
+    pub output: RunOutput,
+    pub attempts: u32,
+    /// The budget ran out (an in-flight attempt may have been killed)
+    pub deadline_exceeded: bool,
+}
+
+/// Retry a command on failure within a total time budget.
+#[tauri::command]
+pub fn run_with_deadline(input: RunDeadlineInput) -> Result<RunDeadlineOutput, String> {
+    let pattern = input.spawn.stderr_regex()?;
+    let (out, attempts, deadline_exceeded) = platform::run_shell_with_deadline(
+        &input.spawn.command,
+        &input.spawn.shell_options(),
+        Duration::from_millis(input.deadline_ms),
+        input.max_attempts,
+    )
+    .map_err(|e| e.to_string())?;
+    let output = RunOutput::from(out).with_parsed_error(pattern.as_ref());
+    Ok(RunDeadlineOutput { output, attempts, deadline_exceeded })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PipelineStageInput {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunPipelineInput {
+    pub stages: Vec<PipelineStageInput>,
+    /// Written to the first stage's stdin
+    pub stdin: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunPipelineOutput {
+    pub status: i32,
+    pub stdout: String,
+    pub stderr: String,
+    /// Exit code of each stage, like `PIPESTATUS`
+    pub stage_statuses: Vec<i32>,
+}
+
+/// Run `a | b | c` without a shell: programs and arguments are passed
+/// verbatim, so nothing in them can inject shell syntax.
+#[tauri::command]
+pub fn run_pipeline(input: RunPipelineInput) -> Result<RunPipelineOutput, String> {
+    let stages: Vec<platform::PipelineStage> = input
+        .stages
+        .into_iter()
+        .map(|s| platform::PipelineStage { program: s.program, args: s.args })
+        .collect();
+    let out = platform::run_pipeline(&stages, input.stdin.as_deref()).map_err(|e| e.to_string())?;
+    Ok(RunPipelineOutput {
+        status: out.status,
+        stdout: out.stdout,
+        stderr: out.stderr,
+        stage_statuses: out.stage_statuses,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunCsvInput {
+    #[serde(flatten)]
+    pub spawn: SpawnInput,
+    pub has_header: bool,
+    /// Defaults to ','
+    pub delimiter: Option<char>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunCsvOutput {
+    /// Exit code; -2 when `timeout_ms` elapsed
+    pub status: i32,
+    /// Keyed by header when `has_header`, otherwise plain rows
+    pub rows: Option<platform::CsvRows>,
+    pub stderr: String,
+    /// Raw stdout, only returned when parsing failed or was skipped
+    pub raw: Option<String>,
+    pub error: Option<String>,
+    /// Same as `RunOutput::oom_killed`
+    pub oom_killed: Option<bool>,
+    pub timed_out: bool,
+}
+
+/// Run a command and parse its stdout as CSV. Output cut short by the
+/// timeout or memory limit isn't parsed, since its last rows may be partial.
+#[tauri::command]
+pub fn run_csv(input: RunCsvInput) -> Result<RunCsvOutput, String> {
+    let out = platform::run_shell_capture(&input.spawn.command, &input.spawn.shell_options())
+        .map_err(|e| e.to_string())?;
+    let parsed = if out.timed_out {
+        Err("command timed out; output is incomplete".to_string())
+    } else if out.oom_killed == Some(true) {
+        Err("command was killed by the memory limit; output is incomplete".to_string())
+    } else {
+        platform::parse_csv(&out.stdout, input.has_header, input.delimiter.unwrap_or(',')).map_err(|e| e.to_string())
+    };
+    let (rows, raw, error) = match parsed {
+        Ok(rows) => (Some(rows), None, None),
+        Err(e) => (None, Some(out.stdout), Some(e)),
+    };
+    Ok(RunCsvOutput {
+        status: if out.timed_out { -2 } else { out.status },
+        rows,
+        stderr: out.stderr,
+        raw,
+        error,
+        oom_killed: out.oom_killed,
+        timed_out: out.timed_out,
+    })
+}
+
+static NEXT_RESULT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Cached results are dropped this long after they were last read.
+const PAGED_RESULT_TTL: Duration = Duration::from_secs(10 * 60);
+
+struct PagedResult {
+    lines: Vec<String>,
+    page_size: usize,
+    last_used: std::time::Instant,
+}
+
+impl PagedResult {
+    fn total_pages(&self) -> usize {
+        self.lines.len().div_ceil(self.page_size)
+    }
+
+    fn page(&self, page: usize) -> Vec<String> {
+        self.lines.chunks(self.page_size).nth(page).map(<[String]>::to_vec).unwrap_or_default()
+    }
+}
+
+/// Output of `run_paginated` commands, by result id. Entries expire
+/// `PAGED_RESULT_TTL` after their last use (checked on every access).
+#[derive(Default)]
+pub struct PagedResults(Mutex<HashMap<u64, PagedResult>>);
+
+impl PagedResults {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<u64, PagedResult>>, String> {
+        let mut results = self.0.lock().map_err(|e| e.to_string())?;
+        results.retain(|_, r| r.last_used.elapsed() < PAGED_RESULT_TTL);
+        Ok(results)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunPaginatedInput {
+    #[serde(flatten)]
+    pub spawn: SpawnInput,
+    /// Lines per page
+    pub page_size: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunPaginatedOutput {
+    pub result_id: u64,
+    pub status: i32,
+    pub stderr: String,
+    /// Page 0
+    pub lines: Vec<String>,
+    pub total_lines: usize,
+    pub total_pages: usize,
+}
+
+/// Run a command and keep its stdout lines server-side, returning the
+/// first page. Fetch the rest with `get_page`; free it with `drop_result`
+/// (or let it expire after 10 minutes unused). The command runs on a
+/// blocking worker, so long runs don't stall the UI.
+#[tauri::command]
+pub async fn run_paginated(
+    results: tauri::State<'_, PagedResults>,
+    input: RunPaginatedInput,
+) -> Result<RunPaginatedOutput, String> {
+    if input.page_size == 0 {
+        return Err("page_size must be at least 1".into());
+    }
+    let opts = input.spawn.shell_options();
+    let command = input.spawn.command;
+    let out = tauri::async_runtime::spawn_blocking(move || platform::run_shell_capture(&command, &opts))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    let result = PagedResult {
+        lines: out.stdout.lines().map(str::to_string).collect(),
+        page_size: input.page_size,
+        last_used: std::time::Instant::now(),
+    };
+    let output = RunPaginatedOutput {
+        result_id: NEXT_RESULT_ID.fetch_add(1, Ordering::Relaxed),
+        status: out.status,
+        stderr: out.stderr,
+        lines: result.page(0),
+        total_lines: result.lines.len(),
+        total_pages: result.total_pages(),
+    };
+    results.lock()?.insert(output.result_id, result);
+    Ok(output)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetPageInput {
+    pub result_id: u64,
+    /// Zero-based
+    pub page: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResultPage {
+    pub page: usize,
+    /// Empty past the last page
+    pub lines: Vec<String>,
+    pub total_lines: usize,
+    pub total_pages: usize,
+}
+
+#[tauri::command]
+pub fn get_page(results: tauri::State<'_, PagedResults>, input: GetPageInput) -> Result<ResultPage, String> {
+    let mut results = results.lock()?;
+    let result = results
+        .get_mut(&input.result_id)
+        .ok_or_else(|| format!("result {} not found (dropped or expired)", input.result_id))?;
+    result.last_used = std::time::Instant::now();
+    Ok(ResultPage {
+        page: input.page,
+        lines: result.page(input.page),
+        total_lines: result.lines.len(),
+        total_pages: result.total_pages(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DropResultInput {
+    pub result_id: u64,
+}
+
+/// Returns false when the result was already gone.
+#[tauri::command]
+pub fn drop_result(results: tauri::State<'_, PagedResults>, input: DropResultInput) -> Result<bool, String> {
+    Ok(results.lock()?.remove(&input.result_id).is_some())
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OpenInput {
     /// URL (https://…) or file path
@@ -57,6 +815,28 @@ pub fn open_path_or_url(input: OpenInput) -> Result<(), String> {
     platform::open_system(&input.target).map_err(|e| e.to_string())
 }
 
+#[derive(Debug, Deserialize)]
+pub struct OpenUrlInInput {
+    pub url: String,
+    /// "firefox" | "chromium" | "chrome" | "brave" | "edge" | "vivaldi" | "opera" | "epiphany" | "safari"
+    pub browser: String,
+}
+
+#[tauri::command]
+pub fn open_url_in(input: OpenUrlInInput) -> Result<(), String> {
+    platform::open_url_in(&input.url, &input.browser).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareFilesInput {
+    pub paths: Vec<String>,
+}
+
+#[tauri::command]
+pub fn share_files(input: ShareFilesInput) -> Result<(), String> {
+    platform::share_files(&input.paths).map_err(|e| e.to_string())
+}
+
 #[derive(Debug, Serialize)]
 pub struct Paths {
     /// e.g., "com.quack.app"
@@ -79,66 +859,1905 @@ pub fn get_paths(app: AppHandle) -> Result<Paths, String> {
     })
 }
 
-/// ----- Window controls -----
+#[tauri::command]
+pub fn get_runtime_dir(app: AppHandle) -> Result<String, String> {
+    platform::runtime_dir(&app.config().identifier)
+        .map(|p| p.display().to_string())
+        .map_err(|e| e.to_string())
+}
 
-#[derive(Debug, Deserialize)]
-pub struct WindowFlag {
-    pub label: Option<String>, // default to "main" if None
-    pub value: bool,
+/// Sockets bound by `create_runtime_socket`; their files are removed on exit.
+#[derive(Default)]
+pub struct RuntimeSockets(Mutex<Vec<RuntimeSocket>>);
+
+struct RuntimeSocket {
+    path: PathBuf,
+    #[cfg(unix)]
+    _listener: std::os::unix::net::UnixListener,
 }
 
-fn get_window<'a>(app: &'a AppHandle, label: Option<String>) -> Result<WebviewWindow, String> {
-    let lbl = label.unwrap_or_else(|| "main".to_string());
-    app.get_webview_window(&lbl)
-        .ok_or_else(|| format!("window '{lbl}' not found"))
+impl RuntimeSockets {
+    pub fn cleanup(&self) {
+        if let Ok(mut sockets) = self.0.lock() {
+            for socket in sockets.drain(..) {
+                let _ = std::fs::remove_file(&socket.path);
+            }
+        }
+    }
 }
 
-#[tauri::command]
-pub fn window_set_always_on_top(app: AppHandle, payload: WindowFlag) -> Result<(), String> {
-    let w = get_window(&app, payload.label)?;
-    platform::set_always_on_top(&w, payload.value).map_err(|e| e.to_string())
+#[derive(Debug, Deserialize)]
+pub struct RuntimeSocketInput {
+    pub name: String,
 }
 
+/// Bind a Unix socket in the runtime dir and return its path.
 #[tauri::command]
-pub fn window_set_decorations(app: AppHandle, payload: WindowFlag) -> Result<(), String> {
-    let w = get_window(&app, payload.label)?;
-    platform::set_decorations(&w, payload.value).map_err(|e| e.to_string())
+pub fn create_runtime_socket(
+    app: AppHandle,
+    sockets: tauri::State<'_, RuntimeSockets>,
+    input: RuntimeSocketInput,
+) -> Result<String, String> {
+    #[cfg(unix)]
+    {
+        let (path, listener) =
+            platform::create_runtime_socket(&app.config().identifier, &input.name)
+                .map_err(|e| e.to_string())?;
+        let mut guard = sockets.0.lock().map_err(|e| e.to_string())?;
+        guard.retain(|s| s.path != path);
+        guard.push(RuntimeSocket { path: path.clone(), _listener: listener });
+        Ok(path.display().to_string())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (app, sockets, input);
+        Err(platform::PlatformError::Unsupported("Unix sockets are not available".into()).to_string())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalkDirInput {
+    pub path: String,
+    pub max_depth: usize,
+    #[serde(default)]
+    pub include_hidden: bool,
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Maximum number of entries returned (default 10 000)
+    pub limit: Option<usize>,
 }
 
 #[tauri::command]
-pub fn window_set_shadow(app: AppHandle, payload: WindowFlag) -> Result<(), String> {
-    let w = get_window(&app, payload.label)?;
-    platform::set_shadow(&w, payload.value).map_err(|e| e.to_string())
+pub fn walk_dir(input: WalkDirInput) -> Result<platform::WalkResult, String> {
+    platform::walk_dir(
+        &input.path,
+        input.max_depth,
+        input.include_hidden,
+        input.follow_symlinks,
+        input.limit.unwrap_or(10_000),
+    )
+    .map_err(|e| e.to_string())
 }
 
 #[derive(Debug, Deserialize)]
-pub struct WindowSize {
-    pub label: Option<String>,
-    pub width: f64,
-    pub height: f64,
+pub struct GlobInput {
+    /// e.g. "/var/log/**/*.log"
+    pub pattern: String,
+    /// Defaults to true
+    pub case_sensitive: Option<bool>,
 }
 
 #[tauri::command]
-pub fn window_resize(app: AppHandle, payload: WindowSize) -> Result<(), String> {
-    let w = get_window(&app, payload.label)?;
-    platform::resize(&w, payload.width, payload.height).map_err(|e| e.to_string())
+pub fn glob(input: GlobInput) -> Result<Vec<String>, String> {
+    platform::expand_glob(&input.pattern, input.case_sensitive.unwrap_or(true))
+        .map_err(|e| e.to_string())
 }
 
 #[derive(Debug, Deserialize)]
-pub struct WindowPosition {
-    pub label: Option<String>,
-    pub x: f64,
-    pub y: f64,
+pub struct StatManyInput {
+    pub paths: Vec<String>,
 }
 
+/// Batch `stat` for change detection; results are in input order.
 #[tauri::command]
-pub fn window_move(app: AppHandle, payload: WindowPosition) -> Result<(), String> {
-    let w = get_window(&app, payload.label)?;
-    platform::move_window(&w, payload.x, payload.y).map_err(|e| e.to_string())
+pub fn stat_many(input: StatManyInput) -> Vec<platform::PathStat> {
+    platform::stat_many(&input.paths)
 }
 
-/// Quit the application
-#[tauri::command]
-pub fn quit_app(app: AppHandle) {
-    app.exit(0);
+static NEXT_READ_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Cancel flags of running `read_file_chunked` reads, by read id.
+#[derive(Default)]
+pub struct FileReads(Mutex<HashMap<u64, Arc<AtomicBool>>>);
+
+#[derive(Debug, Deserialize)]
+pub struct ReadChunkedInput {
+    pub path: String,
+    /// Bytes per chunk (default 256 KiB)
+    pub chunk_size: Option<usize>,
+    /// Start position (default 0)
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChunk {
+    pub read_id: u64,
+    pub offset: u64,
+    /// Base64-encoded bytes
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReadDone {
+    pub read_id: u64,
+    pub bytes_read: u64,
+    pub initial_size: u64,
+    pub final_size: u64,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+/// Stream a file as `file-read://chunk` events followed by one
+/// `file-read://done`. Returns the read id for `cancel_file_read`.
+#[tauri::command]
+pub fn read_file_chunked(
+    app: AppHandle,
+    reads: tauri::State<'_, FileReads>,
+    input: ReadChunkedInput,
+) -> Result<u64, String> {
+    let read_id = NEXT_READ_ID.fetch_add(1, Ordering::Relaxed);
+    let cancel = Arc::new(AtomicBool::new(false));
+    reads.0.lock().map_err(|e| e.to_string())?.insert(read_id, cancel.clone());
+
+    std::thread::spawn(move || {
+        use base64::Engine;
+
+        let result = platform::read_file_chunks(
+            &input.path,
+            input.offset.unwrap_or(0),
+            input.chunk_size.unwrap_or(256 * 1024),
+            &cancel,
+            |offset, bytes| {
+                let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+                platform::events::emit_unlimited(&app, "file-read://chunk", FileChunk { read_id, offset, data });
+            },
+        );
+        if let Ok(mut reads) = app.state::<FileReads>().0.lock() {
+            reads.remove(&read_id);
+        }
+        let done = match result {
+            Ok(r) => FileReadDone {
+                read_id,
+                bytes_read: r.bytes_read,
+                initial_size: r.initial_size,
+                final_size: r.final_size,
+                cancelled: r.cancelled,
+                error: None,
+            },
+            Err(e) => FileReadDone {
+                read_id,
+                bytes_read: 0,
+                initial_size: 0,
+                final_size: 0,
+                cancelled: false,
+                error: Some(e.to_string()),
+            },
+        };
+        platform::events::emit_unlimited(&app, "file-read://done", done);
+    });
+    Ok(read_id)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancelFileReadInput {
+    pub read_id: u64,
+}
+
+/// Returns false when the read has already finished.
+#[tauri::command]
+pub fn cancel_file_read(reads: tauri::State<'_, FileReads>, input: CancelFileReadInput) -> Result<bool, String> {
+    let reads = reads.0.lock().map_err(|e| e.to_string())?;
+    Ok(match reads.get(&input.read_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PathInput {
+    pub path: String,
+}
+
+/// Strict: errors on anything that isn't UTF-8.
+#[tauri::command]
+pub fn read_text_file(input: PathInput) -> Result<String, String> {
+    platform::read_text_file(&input.path).map_err(|e| e.to_string())
+}
+
+/// Decode legacy encodings (Latin-1, Shift_JIS, UTF-16 with BOM, …).
+#[tauri::command]
+pub fn read_text_file_detect(input: PathInput) -> Result<platform::DecodedText, String> {
+    platform::read_text_file_detect(&input.path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn inspect_executable(input: PathInput) -> Result<platform::ExecutableInfo, String> {
+    platform::inspect_executable(&input.path).map_err(|e| e.to_string())
+}
+
+/// Observed watcher latency in milliseconds.
+#[tauri::command]
+pub fn watch_self_test(input: PathInput) -> Result<f64, String> {
+    platform::watch_self_test(&input.path).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RelativePathInput {
+    pub from: String,
+    pub to: String,
+}
+
+#[tauri::command]
+pub fn relative_path(input: RelativePathInput) -> Result<String, String> {
+    platform::relative_path(&input.from, &input.to).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_trash() -> Result<Vec<platform::TrashItem>, String> {
+    platform::list_trash().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreTrashInput {
+    pub name: String,
+    /// When the original path is taken: restore under a new name instead of failing
+    #[serde(default)]
+    pub rename_on_conflict: bool,
+}
+
+/// Returns the path the item was restored to.
+#[tauri::command]
+pub fn restore_from_trash(input: RestoreTrashInput) -> Result<String, String> {
+    platform::restore_from_trash(&input.name, input.rename_on_conflict).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn path_to_uri(input: PathInput) -> Result<String, String> {
+    platform::path_to_uri(&input.path).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UriInput {
+    pub uri: String,
+}
+
+#[tauri::command]
+pub fn uri_to_path(input: UriInput) -> Result<String, String> {
+    platform::uri_to_path(&input.uri).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwapFilesInput {
+    pub a: String,
+    pub b: String,
+}
+
+/// Atomically exchange the contents of two paths.
+#[tauri::command]
+pub fn swap_files(input: SwapFilesInput) -> Result<(), String> {
+    platform::swap_files(&input.a, &input.b).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_mounts() -> Result<Vec<platform::MountInfo>, String> {
+    platform::list_mounts().map_err(|e| e.to_string())
+}
+
+/// ----- Window controls -----
+
+#[derive(Debug, Deserialize)]
+pub struct WindowFlag {
+    pub label: Option<String>, // default to "main" if None
+    pub value: bool,
+}
+
+fn get_window<'a>(app: &'a AppHandle, label: Option<String>) -> Result<WebviewWindow, String> {
+    let lbl = label.unwrap_or_else(|| "main".to_string());
+    app.get_webview_window(&lbl)
+        .ok_or_else(|| format!("window '{lbl}' not found"))
+}
+
+#[tauri::command]
+pub fn window_set_always_on_top(app: AppHandle, payload: WindowFlag) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_always_on_top(&w, payload.value).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn window_set_decorations(app: AppHandle, payload: WindowFlag) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_decorations(&w, payload.value).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn window_set_shadow(app: AppHandle, payload: WindowFlag) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_shadow(&w, payload.value).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn window_set_sticky(app: AppHandle, payload: WindowFlag) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_sticky(&w, payload.value).map_err(|e| e.to_string())
+}
+
+/// Async: the read-back round-trips through the main thread.
+#[tauri::command]
+pub async fn window_is_sticky(app: AppHandle, payload: WindowLabel) -> Result<bool, String> {
+    let w = get_window(&app, payload.label)?;
+    platform::is_sticky(&w).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowShape {
+    pub label: Option<String>,
+    pub regions: Vec<platform::ShapeRect>,
+}
+
+/// Clip the window (and its input) to `regions`; see `platform::set_window_shape`.
+#[tauri::command]
+pub async fn window_set_shape(app: AppHandle, payload: WindowShape) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_window_shape(&w, Some(payload.regions)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn window_clear_shape(app: AppHandle, payload: WindowLabel) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_window_shape(&w, None).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowTheme {
+    pub label: Option<String>,
+    /// "dark" | "light" | "system"
+    pub theme: String,
+}
+
+#[tauri::command]
+pub fn window_set_theme(app: AppHandle, payload: WindowTheme) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_window_theme(&w, &payload.theme).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowCursor {
+    pub label: Option<String>,
+    /// CSS-style name: "default" | "wait" | "progress" | "pointer" | "text" | …
+    pub cursor: String,
+}
+
+/// Set e.g. "wait" before a slow command and "default" afterwards.
+#[tauri::command]
+pub fn window_set_cursor(app: AppHandle, payload: WindowCursor) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_cursor(&w, &payload.cursor).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn window_set_cursor_visible(app: AppHandle, payload: WindowFlag) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_cursor_visible(&w, payload.value).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowSize {
+    pub label: Option<String>,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[tauri::command]
+pub fn window_resize(app: AppHandle, payload: WindowSize) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::resize(&w, payload.width, payload.height).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowPosition {
+    pub label: Option<String>,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[tauri::command]
+pub fn window_move(app: AppHandle, payload: WindowPosition) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::move_window(&w, payload.x, payload.y).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn window_get_state(app: AppHandle, payload: WindowLabel) -> Result<platform::WindowState, String> {
+    let w = get_window(&app, payload.label)?;
+    platform::get_window_state(&w).map_err(|e| e.to_string())
+}
+
+/// Work area (monitor minus panels) the window is on, in physical pixels.
+#[tauri::command]
+pub fn window_get_work_area(app: AppHandle, payload: WindowLabel) -> Result<platform::Rect, String> {
+    let w = get_window(&app, payload.label)?;
+    platform::get_work_area(&w).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowSnap {
+    pub label: Option<String>,
+    /// "left" | "right" | "top" | "bottom" | "top-left" | "top-right" |
+    /// "bottom-left" | "bottom-right" | "maximize"
+    pub position: String,
+}
+
+#[tauri::command]
+pub fn window_snap(app: AppHandle, payload: WindowSnap) -> Result<platform::Rect, String> {
+    let w = get_window(&app, payload.label)?;
+    platform::snap_window(&w, &payload.position).map_err(|e| e.to_string())
+}
+
+/// Position/size that survives compositors reporting stale values right
+/// after a move. Async so the wait happens off the main thread.
+#[tauri::command]
+pub async fn window_get_geometry_stable(
+    app: AppHandle,
+    payload: WindowLabel,
+) -> Result<platform::StableGeometry, String> {
+    let w = get_window(&app, payload.label)?;
+    platform::get_geometry_stable(&w, Duration::from_millis(500)).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowReload {
+    pub label: Option<String>,
+    /// Bypass the HTTP cache for the document
+    pub ignore_cache: Option<bool>,
+}
+
+#[tauri::command]
+pub fn window_reload(app: AppHandle, payload: WindowReload) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::reload(&w, payload.ignore_cache.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowLevel {
+    pub label: Option<String>,
+    /// "normal" | "above" | "below" | "overlay"
+    pub level: String,
+}
+
+#[tauri::command]
+pub fn window_set_level(app: AppHandle, payload: WindowLevel) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_window_level(&w, &payload.level).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowBadge {
+    pub label: Option<String>,
+    /// `None` or 0 clears the badge
+    pub count: Option<i64>,
+}
+
+#[tauri::command]
+pub fn window_set_badge_count(app: AppHandle, payload: WindowBadge) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    platform::set_badge_count(&w, &app.config().identifier, payload.count)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowEvalInput {
+    pub label: Option<String>,
+    pub script: String,
+    /// Don't wait for the result
+    pub fire_and_forget: Option<bool>,
+    /// How long to wait for the result (default 5000)
+    pub timeout_ms: Option<u64>,
+}
+
+type EvalSender = tokio::sync::oneshot::Sender<Result<serde_json::Value, String>>;
+
+/// Result channels of in-flight `window_eval` calls, keyed by eval id.
+#[derive(Default)]
+#[cfg_attr(not(feature = "automation"), allow(dead_code))]
+pub struct PendingEvals(Mutex<HashMap<u64, EvalSender>>);
+
+#[cfg(feature = "automation")]
+static NEXT_EVAL_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Run a script in the webview. Unless `fire_and_forget` is set, the
+/// (awaited) completion value is posted back through `window_eval_result`
+/// and returned as JSON. Requires the `automation` feature.
+#[tauri::command]
+pub async fn window_eval(
+    app: AppHandle,
+    evals: tauri::State<'_, PendingEvals>,
+    payload: WindowEvalInput,
+) -> Result<serde_json::Value, String> {
+    #[cfg(feature = "automation")]
+    {
+        let w = get_window(&app, payload.label)?;
+        if payload.fire_and_forget.unwrap_or(false) {
+            w.eval(&payload.script).map_err(|e| e.to_string())?;
+            return Ok(serde_json::Value::Null);
+        }
+
+        let id = NEXT_EVAL_ID.fetch_add(1, Ordering::Relaxed);
+        let script = serde_json::to_string(&payload.script).map_err(|e| e.to_string())?;
+        // A value IPC can't serialize (cycle, BigInt, …) rejects the invoke;
+        // report that instead of leaving the caller to time out.
+        let wrapped = format!(
+            "(async () => {{ \
+               const send = (ok, value) => window.__TAURI_INTERNALS__.invoke('window_eval_result', {{ payload: {{ id: {id}, ok, value }} }}); \
+               const post = (ok, value) => Promise.resolve().then(() => send(ok, value)) \
+                 .catch((e) => ok ? send(false, 'result is not JSON-serializable: ' + String(e)) : undefined) \
+                 .catch(() => {{}}); \
+               try {{ const v = await (0, eval)({script}); await post(true, v === undefined ? null : v); }} \
+               catch (e) {{ await post(false, String(e)); }} \
+             }})()"
+        );
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        evals.0.lock().map_err(|e| e.to_string())?.insert(id, tx);
+        if let Err(e) = w.eval(&wrapped) {
+            evals.0.lock().map_err(|e| e.to_string())?.remove(&id);
+            return Err(e.to_string());
+        }
+
+        let timeout = Duration::from_millis(payload.timeout_ms.unwrap_or(5000));
+        let result = tokio::time::timeout(timeout, rx).await;
+        evals.0.lock().map_err(|e| e.to_string())?.remove(&id);
+        match result {
+            Ok(Ok(result)) => result,
+            _ => Err(format!("script did not report a result within {}ms", timeout.as_millis())),
+        }
+    }
+
+    #[cfg(not(feature = "automation"))]
+    {
+        let _ = (app, evals, payload);
+        Err("window_eval is not compiled in (enable the `automation` feature)".into())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvalResultInput {
+    pub id: u64,
+    pub ok: bool,
+    pub value: serde_json::Value,
+}
+
+/// Called by the wrapper script injected by `window_eval`.
+#[tauri::command]
+pub fn window_eval_result(evals: tauri::State<'_, PendingEvals>, payload: EvalResultInput) -> Result<(), String> {
+    #[cfg(feature = "automation")]
+    {
+        let tx = evals
+            .0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(&payload.id)
+            .ok_or_else(|| format!("no pending eval with id {}", payload.id))?;
+        let result = if payload.ok {
+            Ok(payload.value)
+        } else {
+            Err(payload.value.as_str().unwrap_or("script threw").to_string())
+        };
+        let _ = tx.send(result);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "automation"))]
+    {
+        let _ = (evals, payload);
+        Err("window_eval is not compiled in (enable the `automation` feature)".into())
+    }
+}
+
+/// Labels of windows whose drag-and-drop events are already forwarded.
+#[derive(Default)]
+pub struct DropWatches(Mutex<HashSet<String>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DropPayload {
+    pub label: String,
+    /// Existing, absolute paths
+    pub paths: Vec<String>,
+    /// Dropped entries that could not be resolved to an existing path
+    pub rejected: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowLabel {
+    pub label: Option<String>,
+}
+
+/// Forward a window's drag-and-drop as `drop://hover`, `drop://drop` and
+/// `drop://cancel` events with backend-validated paths. Idempotent per window.
+#[tauri::command]
+pub fn watch_file_drop(
+    app: AppHandle,
+    watches: tauri::State<'_, DropWatches>,
+    payload: WindowLabel,
+) -> Result<(), String> {
+    let w = get_window(&app, payload.label)?;
+    let label = w.label().to_string();
+    if !watches.0.lock().map_err(|e| e.to_string())?.insert(label.clone()) {
+        return Ok(());
+    }
+
+    let handle = app.clone();
+    w.on_window_event(move |event| {
+        let resolve = |raw: &[PathBuf]| {
+            let mut paths = Vec::new();
+            let mut rejected = Vec::new();
+            for p in raw {
+                match platform::normalize_dropped_path(p) {
+                    Some(abs) => paths.push(abs.display().to_string()),
+                    None => rejected.push(p.display().to_string()),
+                }
+            }
+            DropPayload { label: label.clone(), paths, rejected }
+        };
+        match event {
+            WindowEvent::DragDrop(DragDropEvent::Enter { paths, .. }) => {
+                platform::events::emit(&handle, "drop://hover", resolve(paths));
+            }
+            WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => {
+                platform::events::emit(&handle, "drop://drop", resolve(paths));
+            }
+            WindowEvent::DragDrop(DragDropEvent::Leave) => {
+                platform::events::emit(&handle, "drop://cancel", resolve(&[]));
+            }
+            WindowEvent::Destroyed => {
+                if let Ok(mut set) = handle.state::<DropWatches>().0.lock() {
+                    set.remove(&label);
+                }
+            }
+            _ => {}
+        }
+    });
+    Ok(())
+}
+
+/// Process groups started by `spawn_tied`, keyed by the owning window label.
+#[derive(Default)]
+pub struct TiedProcesses(Mutex<HashMap<String, Vec<u32>>>);
+
+#[derive(Debug, Deserialize)]
+pub struct SpawnTiedInput {
+    #[serde(flatten)]
+    pub spawn: SpawnInput,
+    /// Window whose closing kills the command
+    pub label: String,
+}
+
+/// Spawn a command that is killed (with its children) when the window
+/// `label` is destroyed. Returns the PID.
+#[tauri::command]
+pub fn spawn_tied(
+    app: AppHandle,
+    tied: tauri::State<'_, TiedProcesses>,
+    input: SpawnTiedInput,
+) -> Result<u32, String> {
+    let w = get_window(&app, Some(input.label))?;
+    let label = w.label().to_string();
+
+    let mut child = platform::spawn_shell_group(&input.spawn.command, &input.spawn.shell_options())
+        .map_err(|e| e.to_string())?;
+    let pid = child.id();
+
+    let mut owned = tied.0.lock().map_err(|e| e.to_string())?;
+    // One listener per window; it kills whatever is still registered.
+    if !owned.contains_key(&label) {
+        let handle = app.clone();
+        let window_label = label.clone();
+        w.on_window_event(move |event| {
+            if let WindowEvent::Destroyed = event {
+                let pids = handle
+                    .state::<TiedProcesses>()
+                    .0
+                    .lock()
+                    .ok()
+                    .and_then(|mut map| map.remove(&window_label))
+                    .unwrap_or_default();
+                for pid in pids {
+                    let _ = platform::kill_process_group(pid);
+                }
+            }
+        });
+    }
+    owned.entry(label.clone()).or_default().push(pid);
+    drop(owned);
+
+    // Reap the child and unregister it if it exits before the window closes.
+    std::thread::spawn(move || {
+        let _ = child.wait();
+        if let Ok(mut map) = app.state::<TiedProcesses>().0.lock() {
+            if let Some(pids) = map.get_mut(&label) {
+                pids.retain(|&p| p != pid);
+            }
+        }
+    });
+    Ok(pid)
+}
+
+/// Windows with hide-on-blur set up, by label. A window's event listener
+/// stays installed once added (Tauri can't remove it) but does nothing while
+/// the window isn't `enabled` here.
+#[derive(Default)]
+pub struct HideOnBlur(Mutex<HashMap<String, HideOnBlurEntry>>);
+
+pub struct HideOnBlurEntry {
+    enabled: bool,
+    /// Global shortcut registered to bring the window back
+    shortcut: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HideOnBlurInput {
+    pub label: Option<String>,
+    pub enabled: bool,
+    /// Global shortcut (e.g. "Ctrl+Space") that shows and focuses the
+    /// window, or hides it when it already has focus
+    pub shortcut: Option<String>,
+}
+
+/// Hide a window whenever it loses focus (popup/launcher style), optionally
+/// bringing it back with a global shortcut. Disabling, or closing the
+/// window, unregisters the shortcut.
+#[tauri::command]
+pub fn window_set_hide_on_blur(
+    app: AppHandle,
+    registry: tauri::State<'_, HideOnBlur>,
+    payload: HideOnBlurInput,
+) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+    let w = get_window(&app, payload.label)?;
+    let label = w.label().to_string();
+    let mut windows = registry.0.lock().map_err(|e| e.to_string())?;
+    let installed = windows.contains_key(&label);
+
+    if let Some(old) = windows.get_mut(&label).and_then(|entry| entry.shortcut.take()) {
+        let _ = app.global_shortcut().unregister(old.as_str());
+    }
+    let shortcut = match payload.shortcut.filter(|_| payload.enabled) {
+        Some(shortcut) => {
+            let window_label = label.clone();
+            app.global_shortcut()
+                .on_shortcut(shortcut.as_str(), move |app, _, event| {
+                    if event.state != ShortcutState::Pressed {
+                        return;
+                    }
+                    let Some(w) = app.get_webview_window(&window_label) else { return };
+                    if w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false) {
+                        let _ = w.hide();
+                    } else {
+                        let _ = w.show();
+                        let _ = w.set_focus();
+                    }
+                })
+                .map_err(|e| e.to_string())?;
+            Some(shortcut)
+        }
+        None => None,
+    };
+    windows.insert(label.clone(), HideOnBlurEntry { enabled: payload.enabled, shortcut });
+    drop(windows);
+
+    if !installed {
+        let handle = app.clone();
+        w.on_window_event(move |event| match event {
+            WindowEvent::Focused(false) => {
+                let enabled = handle
+                    .state::<HideOnBlur>()
+                    .0
+                    .lock()
+                    .map(|windows| windows.get(&label).is_some_and(|entry| entry.enabled))
+                    .unwrap_or(false);
+                if let Some(w) = handle.get_webview_window(&label).filter(|_| enabled) {
+                    let _ = w.hide();
+                }
+            }
+            WindowEvent::Destroyed => {
+                let entry = handle.state::<HideOnBlur>().0.lock().ok().and_then(|mut windows| windows.remove(&label));
+                if let Some(shortcut) = entry.and_then(|entry| entry.shortcut) {
+                    let _ = handle.global_shortcut().unregister(shortcut.as_str());
+                }
+            }
+            _ => {}
+        });
+    }
+    Ok(())
+}
+
+/// Quit the application
+#[tauri::command]
+pub fn quit_app(app: AppHandle) {
+    app.exit(0);
+}
+
+/// ----- Process inspection -----
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveFdInput {
+    pub pid: u32,
+    pub fd: i32,
+}
+
+#[tauri::command]
+pub fn resolve_fd(input: ResolveFdInput) -> Result<platform::FdTarget, String> {
+    platform::resolve_fd(input.pid, input.fd).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn is_file_in_use(input: PathInput) -> Result<platform::FileUsers, String> {
+    platform::is_file_in_use(&input.path).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PortOwnerInput {
+    pub port: u16,
+    /// "tcp" | "udp"
+    pub protocol: String,
+}
+
+#[tauri::command]
+pub fn port_owner(input: PortOwnerInput) -> Result<Option<platform::PortOwner>, String> {
+    platform::port_owner(input.port, &input.protocol).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_hostname() -> Result<String, String> {
+    platform::get_hostname().map_err(|e| e.to_string())
+}
+
+/// Hashed, app-specific device id (never the raw machine id).
+#[tauri::command]
+pub fn get_machine_id(app: AppHandle) -> Result<String, String> {
+    platform::get_machine_id(&app.config().identifier).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetHostnameInput {
+    pub hostname: String,
+    /// Set the free-form pretty hostname instead of the static one
+    pub pretty: Option<bool>,
+}
+
+/// Requires the `privileged` feature.
+#[tauri::command]
+pub fn set_hostname(input: SetHostnameInput) -> Result<(), String> {
+    #[cfg(feature = "privileged")]
+    {
+        platform::set_hostname(&input.hostname, input.pretty.unwrap_or(false))
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(feature = "privileged"))]
+    {
+        let _ = input;
+        Err("set_hostname is not compiled in (enable the `privileged` feature)".into())
+    }
+}
+
+#[tauri::command]
+pub fn get_fd_info() -> Result<platform::FdInfo, String> {
+    platform::get_fd_info().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RlimitInput {
+    /// "nofile" | "nproc" | "as" | "core" | "cpu" | "data" | "fsize" | "stack" | "memlock" | "rss"
+    pub resource: String,
+}
+
+#[tauri::command]
+pub fn get_rlimit(input: RlimitInput) -> Result<platform::ResourceLimit, String> {
+    platform::get_rlimit(&input.resource).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRlimitInput {
+    pub resource: String,
+    /// `null` keeps the current value
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+}
+
+/// Returns the limits now in effect.
+#[tauri::command]
+pub fn set_rlimit(input: SetRlimitInput) -> Result<platform::ResourceLimit, String> {
+    platform::set_rlimit(&input.resource, input.soft, input.hard).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProcessTitleInput {
+    pub title: String,
+}
+
+/// Returns what was actually applied to `comm` and the command line.
+#[tauri::command]
+pub fn set_process_title(input: ProcessTitleInput) -> Result<platform::ProcessTitle, String> {
+    platform::set_process_title(&input.title).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PidInput {
+    pub pid: u32,
+}
+
+/// Approximate traffic for a process (see `platform::get_process_network`);
+/// `null` when unavailable.
+#[tauri::command]
+pub fn get_process_network(input: PidInput) -> Result<Option<platform::ProcessNetwork>, String> {
+    platform::get_process_network(input.pid).map_err(|e| e.to_string())
+}
+
+/// ----- Hardware -----
+
+#[tauri::command]
+pub fn get_cpu_scaling() -> Result<Vec<platform::CpuScaling>, String> {
+    platform::get_cpu_scaling().map_err(|e| e.to_string())
+}
+
+/// Per-core usage/frequency/temperature and load average in one call.
+#[tauri::command]
+pub async fn get_cpu_dashboard() -> Result<platform::CpuDashboard, String> {
+    platform::get_cpu_dashboard().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CpuGovernorInput {
+    /// e.g. "performance", "powersave", "schedutil"
+    pub governor: String,
+}
+
+#[tauri::command]
+pub fn set_cpu_governor(input: CpuGovernorInput) -> Result<(), String> {
+    platform::set_cpu_governor(&input.governor).map_err(|e| e.to_string())
+}
+
+/// Stop flag of the running battery monitor thread, if any.
+#[derive(Default)]
+pub struct BatteryMonitor(Mutex<Option<Arc<AtomicBool>>>);
+
+#[derive(Debug, Deserialize)]
+pub struct BatteryMonitorInput {
+    pub warn_percent: f64,
+    pub poll_ms: u64,
+}
+
+/// Poll the battery and emit `battery://low` once per dip below
+/// `warn_percent` (re-armed after recovering 2% above it), plus
+/// `battery://charging` / `battery://discharging` on state changes.
+/// Replaces a monitor that is already running.
+#[tauri::command]
+pub fn start_battery_monitor(
+    app: AppHandle,
+    monitor: tauri::State<'_, BatteryMonitor>,
+    input: BatteryMonitorInput,
+) -> Result<(), String> {
+    if platform::battery_status().map_err(|e| e.to_string())?.is_none() {
+        return Err("No battery found".into());
+    }
+    let stop = Arc::new(AtomicBool::new(false));
+    if let Some(previous) = monitor.0.lock().map_err(|e| e.to_string())?.replace(stop.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let poll = Duration::from_millis(input.poll_ms.max(250));
+    let warn = input.warn_percent;
+    std::thread::spawn(move || {
+        let mut warned = false;
+        let mut last_state: Option<String> = None;
+        while !stop.load(Ordering::Relaxed) {
+            if let Ok(Some(status)) = platform::battery_status() {
+                if !warned && status.percent < warn && status.state != "charging" {
+                    warned = true;
+                    platform::events::emit(&app, "battery://low", &status);
+                } else if warned && status.percent >= warn + 2.0 {
+                    warned = false;
+                }
+
+                let charging = matches!(status.state.as_str(), "charging" | "full");
+                let state = if charging { "charging" } else { "discharging" };
+                if last_state.as_deref().is_some_and(|s| s != state) {
+                    platform::events::emit(&app, &format!("battery://{state}"), &status);
+                }
+                last_state = Some(state.to_string());
+            }
+            std::thread::sleep(poll);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_battery_monitor(monitor: tauri::State<'_, BatteryMonitor>) -> Result<(), String> {
+    if let Some(stop) = monitor.0.lock().map_err(|e| e.to_string())?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Stop flag of the running memory monitor thread, if any.
+#[derive(Default)]
+pub struct MemoryMonitor(Mutex<Option<Arc<AtomicBool>>>);
+
+#[derive(Debug, Deserialize)]
+pub struct MemoryMonitorInput {
+    /// Available-memory percentage below which pressure is "low"
+    pub warn_percent: f64,
+    pub poll_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryPressure {
+    /// "low" | "medium" | "critical"
+    pub level: String,
+    #[serde(flatten)]
+    pub status: platform::MemoryStatus,
+}
+
+/// Emit `memory://pressure` whenever the pressure level changes to "low"
+/// (available below `warn_percent`), "medium" (below half of it) or
+/// "critical" (below a quarter). Where PSI is available, stalls raise the
+/// level too: some-stall ≥ 10% is medium, full-stall ≥ 5% is critical.
+/// Replaces a monitor that is already running.
+#[tauri::command]
+pub fn start_memory_monitor(
+    app: AppHandle,
+    monitor: tauri::State<'_, MemoryMonitor>,
+    input: MemoryMonitorInput,
+) -> Result<(), String> {
+    platform::memory_status().map_err(|e| e.to_string())?;
+    let stop = Arc::new(AtomicBool::new(false));
+    if let Some(previous) = monitor.0.lock().map_err(|e| e.to_string())?.replace(stop.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let poll = Duration::from_millis(input.poll_ms.max(250));
+    let warn = input.warn_percent;
+    std::thread::spawn(move || {
+        let mut last_level: Option<&'static str> = None;
+        while !stop.load(Ordering::Relaxed) {
+            if let Ok(status) = platform::memory_status() {
+                let free = status.available_percent;
+                let some = status.psi_some_avg10.unwrap_or(0.0);
+                let full = status.psi_full_avg10.unwrap_or(0.0);
+                let level = if free < warn / 4.0 || full >= 5.0 {
+                    Some("critical")
+                } else if free < warn / 2.0 || some >= 10.0 {
+                    Some("medium")
+                } else if free < warn {
+                    Some("low")
+                } else {
+                    None
+                };
+                if let Some(name) = level.filter(|_| level != last_level) {
+                    platform::events::emit(&app, "memory://pressure", MemoryPressure { level: name.into(), status });
+                }
+                last_level = level;
+            }
+            std::thread::sleep(poll);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_memory_monitor(monitor: tauri::State<'_, MemoryMonitor>) -> Result<(), String> {
+    if let Some(stop) = monitor.0.lock().map_err(|e| e.to_string())?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Stop flag of the watcher behind `power-profile://changed`, started the
+/// first time the profile is read or set.
+#[derive(Default)]
+pub struct PowerProfileWatch(Mutex<Option<Arc<AtomicBool>>>);
+
+impl PowerProfileWatch {
+    /// Start the watcher unless one is running. A watcher whose `gdbus`
+    /// exited has its stop flag set and is replaced.
+    fn ensure(&self, app: &AppHandle) -> Result<(), String> {
+        let mut running = self.0.lock().map_err(|e| e.to_string())?;
+        if running.as_ref().is_some_and(|stop| !stop.load(Ordering::Relaxed)) {
+            return Ok(());
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let app = app.clone();
+        platform::watch_power_profile(stop.clone(), move |profile| {
+            platform::events::emit(&app, "power-profile://changed", &profile);
+        })
+        .map_err(|e| e.to_string())?;
+        *running = Some(stop);
+        Ok(())
+    }
+}
+
+/// Also starts the `power-profile://changed` watcher.
+#[tauri::command]
+pub fn get_power_profile(
+    app: AppHandle,
+    watch: tauri::State<'_, PowerProfileWatch>,
+) -> Result<platform::PowerProfiles, String> {
+    let profiles = platform::get_power_profile().map_err(|e| e.to_string())?;
+    let _ = watch.ensure(&app);
+    Ok(profiles)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PowerProfileInput {
+    /// "power-saver" | "balanced" | "performance"
+    pub profile: String,
+}
+
+/// Switch the power profile. `power-profile://changed` comes from the
+/// watcher, which sees changes made anywhere; if it can't run, the event
+/// is sent for this change only.
+#[tauri::command]
+pub fn set_power_profile(
+    app: AppHandle,
+    watch: tauri::State<'_, PowerProfileWatch>,
+    input: PowerProfileInput,
+) -> Result<(), String> {
+    let watching = watch.ensure(&app).is_ok();
+    platform::set_power_profile(&input.profile).map_err(|e| e.to_string())?;
+    if !watching {
+        platform::events::emit(&app, "power-profile://changed", &input.profile);
+    }
+    Ok(())
+}
+
+/// Milliseconds since the last keyboard/mouse input.
+#[tauri::command]
+pub fn get_idle_time() -> Result<u64, String> {
+    platform::get_idle_time_ms().map_err(|e| e.to_string())
+}
+
+static NEXT_IDLE_WATCH_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Stop flags of running `on_idle` watches, by watch id.
+#[derive(Default)]
+pub struct IdleWatches(Mutex<HashMap<u64, Arc<AtomicBool>>>);
+
+#[derive(Debug, Deserialize)]
+pub struct OnIdleInput {
+    /// Idle threshold
+    pub seconds: u64,
+    pub command: SpawnInput,
+    /// Fire again after each return from activity; otherwise fire once
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IdleFired {
+    pub idle_watch_id: u64,
+    pub idle_ms: u64,
+    /// Set when the command failed to start
+    pub error: Option<String>,
+}
+
+/// Run a command once the user has been idle for `seconds`, emitting
+/// `idle://fired`. Returns the id for `cancel_idle_watch`.
+#[tauri::command]
+pub fn on_idle(
+    app: AppHandle,
+    watches: tauri::State<'_, IdleWatches>,
+    input: OnIdleInput,
+) -> Result<u64, String> {
+    // Fail now rather than in the background if idle time can't be read.
+    platform::get_idle_time_ms().map_err(|e| e.to_string())?;
+
+    let idle_watch_id = NEXT_IDLE_WATCH_ID.fetch_add(1, Ordering::Relaxed);
+    let stop = Arc::new(AtomicBool::new(false));
+    watches.0.lock().map_err(|e| e.to_string())?.insert(idle_watch_id, stop.clone());
+
+    let threshold_ms = input.seconds.saturating_mul(1000);
+    let poll = Duration::from_millis((threshold_ms / 4).clamp(250, 1000));
+    let opts = input.command.shell_options();
+    let command = input.command.command;
+    let repeat = input.repeat;
+    std::thread::spawn(move || {
+        let mut armed = true;
+        while !stop.load(Ordering::Relaxed) {
+            let Ok(idle_ms) = platform::get_idle_time_ms() else {
+                std::thread::sleep(poll);
+                continue;
+            };
+            if armed && idle_ms >= threshold_ms {
+                armed = false;
+                let error = platform::spawn_shell(&command, &opts).err().map(|e| e.to_string());
+                platform::events::emit(&app, "idle://fired", IdleFired { idle_watch_id, idle_ms, error });
+                if !repeat {
+                    break;
+                }
+            } else if !armed && idle_ms < threshold_ms {
+                armed = true;
+            }
+            std::thread::sleep(poll);
+        }
+        if let Ok(mut watches) = app.state::<IdleWatches>().0.lock() {
+            watches.remove(&idle_watch_id);
+        }
+    });
+    Ok(idle_watch_id)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancelIdleWatchInput {
+    pub idle_watch_id: u64,
+}
+
+/// Returns false when the watch has already ended.
+#[tauri::command]
+pub fn cancel_idle_watch(
+    watches: tauri::State<'_, IdleWatches>,
+    input: CancelIdleWatchInput,
+) -> Result<bool, String> {
+    let watches = watches.0.lock().map_err(|e| e.to_string())?;
+    Ok(match watches.get(&input.idle_watch_id) {
+        Some(stop) => {
+            stop.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
+
+#[tauri::command]
+pub fn get_idle_timeout() -> Result<u32, String> {
+    platform::get_idle_timeout().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdleTimeoutInput {
+    /// 0 disables blanking
+    pub seconds: u32,
+}
+
+#[tauri::command]
+pub fn set_idle_timeout(input: IdleTimeoutInput) -> Result<(), String> {
+    platform::set_idle_timeout(input.seconds).map_err(|e| e.to_string())
+}
+
+/// Hardware video decode/encode support; all false when none is found.
+#[tauri::command]
+pub async fn get_video_accel() -> platform::VideoAccel {
+    platform::get_video_accel()
+}
+
+/// ----- Displays -----
+
+/// Monitors are indexed in the order `get_display_scale` lists them with no
+/// selector: kscreen's output order on KDE, Mutter's logical-monitor order on
+/// GNOME. Indices can shift when outputs are plugged or unplugged, so
+/// `connector` is the stabler choice where the frontend has it.
+#[derive(Debug, Deserialize)]
+pub struct DisplayScaleQuery {
+    pub monitor_index: Option<usize>,
+    /// Connector name, e.g. "eDP-1"; used when `monitor_index` is absent
+    pub connector: Option<String>,
+}
+
+impl DisplayScaleQuery {
+    /// The selected monitor out of `scales`, or `None` when nothing is selected.
+    fn select<'a>(&self, scales: &'a [platform::DisplayScale]) -> Result<Option<&'a platform::DisplayScale>, String> {
+        if let Some(index) = self.monitor_index {
+            return scales
+                .get(index)
+                .map(Some)
+                .ok_or_else(|| format!("No monitor at index {index} ({} active)", scales.len()));
+        }
+        match &self.connector {
+            Some(connector) => scales
+                .iter()
+                .find(|s| &s.connector == connector)
+                .map(Some)
+                .ok_or_else(|| format!("No active monitor on connector '{connector}'")),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Scale factors of all active outputs, or just the selected one.
+#[tauri::command]
+pub fn get_display_scale(input: Option<DisplayScaleQuery>) -> Result<Vec<platform::DisplayScale>, String> {
+    let scales = platform::get_display_scales().map_err(|e| e.to_string())?;
+    let selected = match &input {
+        Some(query) => query.select(&scales)?.cloned(),
+        None => None,
+    };
+    Ok(selected.map(|s| vec![s]).unwrap_or(scales))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetDisplayScaleInput {
+    /// Either `monitor_index` or `connector` is required
+    #[serde(flatten)]
+    pub monitor: DisplayScaleQuery,
+    pub scale: f64,
+}
+
+#[tauri::command]
+pub fn set_display_scale(input: SetDisplayScaleInput) -> Result<(), String> {
+    let connector = match input.monitor.monitor_index {
+        Some(_) => {
+            let scales = platform::get_display_scales().map_err(|e| e.to_string())?;
+            input.monitor.select(&scales)?.map(|s| s.connector.clone())
+        }
+        None => input.monitor.connector.clone(),
+    }
+    .ok_or("monitor_index or connector is required")?;
+    platform::set_display_scale(&connector, input.scale).map_err(|e| e.to_string())
+}
+
+/// ----- Icons -----
+
+#[tauri::command]
+pub fn get_icon_theme() -> String {
+    platform::get_icon_theme()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveIconInput {
+    /// Icon name, e.g. "text-x-generic" or "firefox"
+    pub name: String,
+    /// Preferred pixel size, e.g. 48
+    pub size: u32,
+}
+
+/// Path to the best-matching icon file, or `null` when none is found.
+#[tauri::command]
+pub fn resolve_icon(input: ResolveIconInput) -> Option<String> {
+    platform::resolve_icon(&input.name, input.size)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveAppIconInput {
+    /// e.g. "org.mozilla.firefox.desktop" (the suffix is optional)
+    pub desktop_id: String,
+    pub size: u32,
+}
+
+/// Icon file for an installed application, or `null` for a placeholder.
+#[tauri::command]
+pub fn resolve_app_icon(input: ResolveAppIconInput) -> Option<String> {
+    platform::resolve_app_icon(&input.desktop_id, input.size)
+}
+
+#[tauri::command]
+pub fn get_kbd_backlight() -> Result<platform::KbdBacklight, String> {
+    platform::get_kbd_backlight().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KbdBacklightInput {
+    /// Absolute level, clamped to the device max
+    pub brightness: u32,
+}
+
+/// Returns the level actually applied.
+#[tauri::command]
+pub fn set_kbd_backlight(input: KbdBacklightInput) -> Result<u32, String> {
+    platform::set_kbd_backlight(input.brightness).map_err(|e| e.to_string())
+}
+
+/// ----- Input devices -----
+
+#[derive(Debug, Deserialize)]
+pub struct TouchpadInput {
+    /// xinput id or name substring; required when several touchpads exist
+    pub device: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_touchpad_enabled(input: Option<TouchpadInput>) -> Result<bool, String> {
+    let device = input.and_then(|i| i.device);
+    platform::get_touchpad_enabled(device.as_deref()).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetTouchpadInput {
+    pub enabled: bool,
+    pub device: Option<String>,
+}
+
+#[tauri::command]
+pub fn set_touchpad_enabled(input: SetTouchpadInput) -> Result<(), String> {
+    platform::set_touchpad_enabled(input.enabled, input.device.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Empty when devices can't be enumerated.
+#[tauri::command]
+pub fn list_input_devices() -> Vec<platform::InputDevice> {
+    platform::list_input_devices()
+}
+
+#[tauri::command]
+pub fn get_gesture_settings() -> Result<platform::GestureSettings, String> {
+    platform::get_gesture_settings().map_err(|e| e.to_string())
+}
+
+/// Only the fields provided are changed; returns the resulting settings.
+#[tauri::command]
+pub fn set_gesture_settings(input: platform::GestureUpdate) -> Result<platform::GestureSettings, String> {
+    platform::set_gesture_settings(input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_key_repeat() -> Result<platform::KeyRepeat, String> {
+    platform::get_key_repeat().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeyRepeatInput {
+    pub delay_ms: u32,
+    pub rate_hz: u32,
+}
+
+/// Values are clamped to sane ranges; returns what was applied.
+#[tauri::command]
+pub fn set_key_repeat(input: KeyRepeatInput) -> Result<platform::KeyRepeat, String> {
+    platform::set_key_repeat(input.delay_ms, input.rate_hz).map_err(|e| e.to_string())
+}
+
+/// ----- Notifications -----
+
+#[derive(Debug, Deserialize)]
+pub struct NotifyInput {
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    /// "low" | "normal" | "critical"
+    pub urgency: Option<String>,
+    /// -1 = server default, 0 = never expire
+    pub timeout_ms: Option<i32>,
+}
+
+/// Show a notification; returns its id for `close_notification`.
+#[tauri::command]
+pub fn notify(input: NotifyInput) -> Result<u32, String> {
+    platform::notify(&input.title, &input.body, input.urgency.as_deref(), input.timeout_ms)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloseNotificationInput {
+    pub id: u32,
+}
+
+#[tauri::command]
+pub fn close_notification(input: CloseNotificationInput) -> Result<(), String> {
+    platform::close_notification(input.id).map_err(|e| e.to_string())
+}
+
+/// ----- Input methods -----
+
+#[tauri::command]
+pub fn get_input_method() -> Result<platform::InputMethodState, String> {
+    platform::get_input_method().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InputMethodInput {
+    pub enabled: bool,
+}
+
+#[tauri::command]
+pub fn set_input_method_enabled(input: InputMethodInput) -> Result<(), String> {
+    platform::set_input_method_enabled(input.enabled).map_err(|e| e.to_string())
+}
+
+/// ----- Lock keys -----
+
+#[tauri::command]
+pub fn get_lock_keys() -> Result<platform::LockKeys, String> {
+    platform::get_lock_keys().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LockKeyInput {
+    /// "caps" | "num" | "scroll"
+    pub key: String,
+    pub on: bool,
+}
+
+#[tauri::command]
+pub fn set_lock_key(input: LockKeyInput) -> Result<(), String> {
+    platform::set_lock_key(&input.key, input.on).map_err(|e| e.to_string())
+}
+
+/// ----- Accessibility -----
+
+/// Stop flag of the watcher behind `motion://changed`, started the first
+/// time the preference is read or set.
+#[derive(Default)]
+pub struct MotionWatch(Mutex<Option<Arc<AtomicBool>>>);
+
+impl MotionWatch {
+    /// Start the watcher unless one is running; see `PowerProfileWatch::ensure`.
+    fn ensure(&self, app: &AppHandle) -> Result<(), String> {
+        let mut running = self.0.lock().map_err(|e| e.to_string())?;
+        if running.as_ref().is_some_and(|stop| !stop.load(Ordering::Relaxed)) {
+            return Ok(());
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let app = app.clone();
+        platform::watch_reduced_motion(stop.clone(), move |reduced| {
+            platform::events::emit(&app, "motion://changed", reduced);
+        })
+        .map_err(|e| e.to_string())?;
+        *running = Some(stop);
+        Ok(())
+    }
+}
+
+/// Also starts the `motion://changed` watcher.
+#[tauri::command]
+pub fn get_reduced_motion(app: AppHandle, watch: tauri::State<'_, MotionWatch>) -> Result<bool, String> {
+    let reduced = platform::get_reduced_motion().map_err(|e| e.to_string())?;
+    let _ = watch.ensure(&app);
+    Ok(reduced)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReducedMotionInput {
+    pub enabled: bool,
+}
+
+/// Set the reduced-motion preference. `motion://changed` comes from the
+/// watcher, which also sees changes made in system settings; if it can't
+/// run, the event is sent for this change only.
+#[tauri::command]
+pub fn set_reduced_motion(
+    app: AppHandle,
+    watch: tauri::State<'_, MotionWatch>,
+    input: ReducedMotionInput,
+) -> Result<(), String> {
+    let watching = watch.ensure(&app).is_ok();
+    platform::set_reduced_motion(input.enabled).map_err(|e| e.to_string())?;
+    if !watching {
+        platform::events::emit(&app, "motion://changed", input.enabled);
+    }
+    Ok(())
+}
+
+/// ----- Events -----
+
+#[derive(Debug, Deserialize)]
+pub struct EventRateLimitInput {
+    /// Per-channel cap; `None` or 0 removes the limit
+    pub events_per_sec: Option<u32>,
+}
+
+/// Cap how many events per second each channel may emit (per stream for
+/// `cmd://stdout`/`cmd://stderr`). Excess events are dropped and summarized
+/// on `events://dropped`. Lifecycle events (`cmd://exit`, `io://…/end`) and
+/// `file-read://` chunks are never limited.
+#[tauri::command]
+pub fn set_event_rate_limit(
+    limiter: tauri::State<'_, platform::events::EventLimiter>,
+    input: EventRateLimitInput,
+) {
+    limiter.set_limit(input.events_per_sec);
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TestEventInput {
+    pub channel: String,
+    pub payload: serde_json::Value,
+    pub delay_ms: Option<u64>,
+}
+
+/// Emit an arbitrary payload on `channel` (optionally after a delay) to
+/// check that frontend listeners are wired up.
+#[tauri::command]
+pub fn emit_test_event(app: AppHandle, input: TestEventInput) {
+    match input.delay_ms {
+        Some(ms) if ms > 0 => {
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(ms));
+                platform::events::emit(&app, &input.channel, input.payload);
+            });
+        }
+        _ => platform::events::emit(&app, &input.channel, input.payload),
+    }
+}
+
+/// ----- Keyring -----
+
+#[derive(Deserialize)]
+pub struct KeyringSetInput {
+    pub service: String,
+    pub account: String,
+    pub secret: String,
+}
+
+// Hand-written so the secret can never end up in a log line.
+impl std::fmt::Debug for KeyringSetInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyringSetInput")
+            .field("service", &self.service)
+            .field("account", &self.account)
+            .field("secret", &"***")
+            .finish()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeyringEntryInput {
+    pub service: String,
+    pub account: String,
+}
+
+#[tauri::command]
+pub fn keyring_set(input: KeyringSetInput) -> Result<(), String> {
+    platform::keyring_set(&input.service, &input.account, &input.secret).map_err(|e| e.to_string())
+}
+
+/// `null` when no secret is stored for the entry.
+#[tauri::command]
+pub fn keyring_get(input: KeyringEntryInput) -> Result<Option<String>, String> {
+    platform::keyring_get(&input.service, &input.account).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn keyring_delete(input: KeyringEntryInput) -> Result<(), String> {
+    platform::keyring_delete(&input.service, &input.account).map_err(|e| e.to_string())
+}
+
+/// ----- Versions -----
+
+#[derive(Debug, Deserialize)]
+pub struct CompareVersionsInput {
+    pub a: String,
+    pub b: String,
+}
+
+/// Compare two version strings; returns -1, 0 or 1.
+#[tauri::command]
+pub fn compare_versions(input: CompareVersionsInput) -> Result<i8, String> {
+    platform::compare_versions(&input.a, &input.b).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VersionSatisfiesInput {
+    pub version: String,
+    /// e.g. ">=1.2, <2"
+    pub requirement: String,
+}
+
+#[tauri::command]
+pub fn version_satisfies(input: VersionSatisfiesInput) -> Result<bool, String> {
+    platform::version_satisfies(&input.version, &input.requirement).map_err(|e| e.to_string())
+}
+
+/// ----- Updates -----
+
+/// Read-only check for pending package updates.
+#[tauri::command]
+pub async fn get_pending_updates() -> Result<platform::PendingUpdates, String> {
+    platform::get_pending_updates().map_err(|e| e.to_string())
+}
+
+/// ----- Portals -----
+
+#[derive(Debug, Deserialize)]
+pub struct PortalPermissionInput {
+    /// "camera" | "microphone" | "speakers" | "location" | "notifications" | "screenshot" | "background"
+    pub portal: String,
+}
+
+/// "granted" | "denied" | "ask" | "unknown". Under Flatpak this needs the
+/// `--talk-name=org.freedesktop.impl.portal.PermissionStore` finish-arg.
+#[tauri::command]
+pub fn check_portal_permission(input: PortalPermissionInput) -> Result<String, String> {
+    platform::check_portal_permission(&input.portal)
+        .map(str::to_string)
+        .map_err(|e| e.to_string())
+}
+
+/// ----- Window manager -----
+
+#[tauri::command]
+pub fn get_wm_capabilities() -> Result<platform::WmCapabilities, String> {
+    platform::get_wm_capabilities().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WindowFeatureInput {
+    /// "transparency" | "always_on_top" | "decorations" | "shape" |
+    /// "click_through" | "sticky" | "progress" | "badge"
+    pub feature: String,
+}
+
+/// "yes" | "no" | "unknown" for whether the feature works in this session.
+#[tauri::command]
+pub fn window_feature_supported(input: WindowFeatureInput) -> Result<&'static str, String> {
+    platform::window_feature_supported(&input.feature).map_err(|e| e.to_string())
+}
+
+/// ----- Clipboard -----
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ClipboardFormatsInput {
+    /// Query the primary (middle-click) selection instead
+    #[serde(default)]
+    pub primary: bool,
+}
+
+/// MIME types on offer; empty when the clipboard is empty.
+#[tauri::command]
+pub fn clipboard_available_formats(input: Option<ClipboardFormatsInput>) -> Result<Vec<String>, String> {
+    let input = input.unwrap_or_default();
+    platform::clipboard_available_formats(input.primary).map_err(|e| e.to_string())
+}
+
+/// Paths copied or cut in a file manager; empty when there are none.
+#[tauri::command]
+pub fn clipboard_read_files() -> Result<Vec<String>, String> {
+    platform::clipboard_read_files().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClipboardWriteFilesInput {
+    pub paths: Vec<String>,
+    /// Paste moves instead of copies (GNOME-family file managers)
+    #[serde(default)]
+    pub cut: bool,
+}
+
+#[tauri::command]
+pub fn clipboard_write_files(input: ClipboardWriteFilesInput) -> Result<(), String> {
+    platform::clipboard_write_files(&input.paths, input.cut).map_err(|e| e.to_string())
+}
+
+static NEXT_CLIPBOARD_WATCH_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Stop flags of running clipboard watches.
+#[derive(Default)]
+pub struct ClipboardWatches(Mutex<HashMap<u64, Arc<AtomicBool>>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardWatch {
+    pub watch_id: u64,
+    /// "wl-paste" | "clipnotify" (native notifications) or "poll"
+    pub mode: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardChanged {
+    pub watch_id: u64,
+    pub formats: Vec<String>,
+}
+
+/// Emit `clipboard://changed` with the offered formats on every clipboard
+/// change. Stop with `unwatch_clipboard`.
+#[tauri::command]
+pub async fn watch_clipboard(
+    app: AppHandle,
+    watches: tauri::State<'_, ClipboardWatches>,
+) -> Result<ClipboardWatch, String> {
+    let watch_id = NEXT_CLIPBOARD_WATCH_ID.fetch_add(1, Ordering::Relaxed);
+    let stop = Arc::new(AtomicBool::new(false));
+    let mode = platform::watch_clipboard(stop.clone(), move |formats| {
+        platform::events::emit(&app, "clipboard://changed", ClipboardChanged { watch_id, formats });
+    })
+    .map_err(|e| e.to_string())?;
+    watches.0.lock().map_err(|e| e.to_string())?.insert(watch_id, stop);
+    Ok(ClipboardWatch { watch_id, mode })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnwatchClipboardInput {
+    pub watch_id: u64,
+}
+
+/// Returns false for an unknown or already stopped watch.
+#[tauri::command]
+pub fn unwatch_clipboard(
+    watches: tauri::State<'_, ClipboardWatches>,
+    input: UnwatchClipboardInput,
+) -> Result<bool, String> {
+    let stop = watches.0.lock().map_err(|e| e.to_string())?.remove(&input.watch_id);
+    Ok(match stop {
+        Some(stop) => {
+            stop.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
+
+/// ----- Firewall -----
+
+#[tauri::command]
+pub fn get_firewall_status() -> Result<platform::FirewallStatus, String> {
+    platform::get_firewall_status().map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FirewallInput {
+    pub enabled: bool,
+}
+
+/// Requires the `privileged` feature.
+#[tauri::command]
+pub fn set_firewall_enabled(input: FirewallInput) -> Result<(), String> {
+    #[cfg(feature = "privileged")]
+    {
+        platform::set_firewall_enabled(input.enabled).map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(feature = "privileged"))]
+    {
+        let _ = input;
+        Err("set_firewall_enabled is not compiled in (enable the `privileged` feature)".into())
+    }
+}
+
+/// ----- File batches -----
+
+#[derive(Debug, Deserialize)]
+pub struct FsBatchInput {
+    pub ops: Vec<platform::FsOp>,
+}
+
+/// Copy/move/delete/mkdir in order, rolling back on failure where possible.
+#[tauri::command]
+pub async fn fs_batch(input: FsBatchInput) -> platform::FsBatchResult {
+    platform::fs_batch(&input.ops)
+}
+
+/// ----- Audio -----
+
+/// Apps currently playing or recording audio (all idle without a sound server).
+#[tauri::command]
+pub async fn get_audio_activity() -> platform::AudioActivity {
+    platform::get_audio_activity()
+}
+
+/// ----- Editors -----
+
+#[derive(Debug, Deserialize)]
+pub struct OpenInEditorInput {
+    pub path: String,
+    pub line: Option<u32>,
+}
+
+/// Open a file in `$VISUAL`/`$EDITOR` or the default text editor.
+#[tauri::command]
+pub fn open_in_editor(input: OpenInEditorInput) -> Result<platform::EditorLaunch, String> {
+    platform::open_in_editor(&input.path, input.line).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_terminals() -> Vec<platform::TerminalInfo> {
+    platform::list_terminals()
+}
+
+/// `null` when no terminal emulator is installed.
+#[tauri::command]
+pub fn get_default_terminal() -> Option<platform::TerminalInfo> {
+    platform::get_default_terminal()
 }
@@ -3,23 +3,197 @@
 mod platform;
 mod commands;
 
+use tauri::{Manager, RunEvent};
+
 fn main() {
+    // `--hidden` (e.g. from an autostart entry) keeps the main window hidden.
+    let hidden = std::env::args().any(|a| a == "--hidden");
+    let mut startup = commands::StartupOptions::from_args().with_startup_hidden(hidden);
+    // `--audit-log <path>` records every shell command the app runs;
+    // `--audit-redact '*_KEY,AWS_*'` redacts more variables than the defaults.
+    let args: Vec<String> = std::env::args().collect();
+    let flag = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+    if let Some(path) = flag("--audit-log") {
+        startup = startup.with_command_audit(path.into());
+    }
+    if let Some(list) = flag("--audit-redact") {
+        let patterns = list.split(',').map(str::trim).filter(|p| !p.is_empty()).map(str::to_string).collect();
+        startup = startup.with_audit_redact(patterns);
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .manage(commands::AppStart::now())
+        .manage(startup.clone())
+        .manage(platform::events::EventLimiter::default())
+        .manage(commands::DropWatches::default())
+        .manage(commands::RuntimeSockets::default())
+        .manage(commands::BatteryMonitor::default())
+        .manage(commands::MemoryMonitor::default())
+        .manage(commands::RunningStreams::default())
+        .manage(commands::TiedProcesses::default())
+        .manage(commands::FileReads::default())
+        .manage(commands::IdleWatches::default())
+        .manage(commands::InteractiveSessions::default())
+        .manage(commands::ClipboardWatches::default())
+        .manage(commands::HideOnBlur::default())
+        .manage(commands::PagedResults::default())
+        .manage(commands::PendingEvals::default())
+        .manage(commands::PowerProfileWatch::default())
+        .manage(commands::MotionWatch::default())
+        .setup(move |app| {
+            startup.apply(app.handle())?;
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::get_platform,
+            commands::get_diagnostics,
+            commands::get_app_uptime,
+            commands::get_launch_args,
+            commands::get_startup_args,
+            commands::get_audit_log_path,
+            commands::get_enabled_features,
             commands::open_path_or_url,
+            commands::open_url_in,
+            commands::share_files,
+            commands::list_shells,
             commands::spawn,
+            commands::spawn_tied,
+            commands::window_set_hide_on_blur,
             commands::run,
+            commands::run_stream,
+            commands::cancel_stream,
+            commands::start_interactive,
+            commands::write_stdin,
+            commands::close_interactive,
+            commands::run_with_deadline,
+            commands::run_csv,
+            commands::run_paginated,
+            commands::get_page,
+            commands::drop_result,
+            commands::run_pipeline,
             commands::get_paths,
+            commands::get_runtime_dir,
+            commands::create_runtime_socket,
+            commands::walk_dir,
+            commands::glob,
+            commands::stat_many,
+            commands::inspect_executable,
+            commands::read_text_file,
+            commands::read_text_file_detect,
+            commands::read_file_chunked,
+            commands::cancel_file_read,
+            commands::path_to_uri,
+            commands::uri_to_path,
+            commands::relative_path,
+            commands::watch_self_test,
+            commands::list_trash,
+            commands::restore_from_trash,
+            commands::swap_files,
+            commands::list_mounts,
             commands::window_set_always_on_top,
             commands::window_set_decorations,
             commands::window_set_shadow,
+            commands::window_set_sticky,
+            commands::window_is_sticky,
+            commands::window_set_shape,
+            commands::window_clear_shape,
+            commands::window_set_theme,
+            commands::window_set_cursor,
+            commands::window_set_cursor_visible,
             commands::window_resize,
             commands::window_move,
+            commands::window_get_state,
+            commands::window_get_work_area,
+            commands::window_snap,
+            commands::window_get_geometry_stable,
+            commands::window_reload,
+            commands::window_set_level,
+            commands::window_set_badge_count,
+            commands::window_eval,
+            commands::window_eval_result,
+            commands::watch_file_drop,
             commands::quit_app,
+            commands::resolve_fd,
+            commands::port_owner,
+            commands::is_file_in_use,
+            commands::get_hostname,
+            commands::set_hostname,
+            commands::get_machine_id,
+            commands::get_fd_info,
+            commands::get_rlimit,
+            commands::set_rlimit,
+            commands::set_process_title,
+            commands::get_process_network,
+            commands::get_cpu_scaling,
+            commands::get_cpu_dashboard,
+            commands::set_cpu_governor,
+            commands::start_battery_monitor,
+            commands::stop_battery_monitor,
+            commands::start_memory_monitor,
+            commands::stop_memory_monitor,
+            commands::get_power_profile,
+            commands::set_power_profile,
+            commands::get_idle_time,
+            commands::on_idle,
+            commands::cancel_idle_watch,
+            commands::get_idle_timeout,
+            commands::set_idle_timeout,
+            commands::get_video_accel,
+            commands::get_display_scale,
+            commands::set_display_scale,
+            commands::get_icon_theme,
+            commands::resolve_icon,
+            commands::resolve_app_icon,
+            commands::get_kbd_backlight,
+            commands::set_kbd_backlight,
+            commands::list_input_devices,
+            commands::get_touchpad_enabled,
+            commands::set_touchpad_enabled,
+            commands::get_gesture_settings,
+            commands::set_gesture_settings,
+            commands::get_key_repeat,
+            commands::set_key_repeat,
+            commands::notify,
+            commands::close_notification,
+            commands::get_input_method,
+            commands::set_input_method_enabled,
+            commands::get_lock_keys,
+            commands::set_lock_key,
+            commands::get_reduced_motion,
+            commands::set_reduced_motion,
+            commands::set_event_rate_limit,
+            commands::emit_test_event,
+            commands::keyring_set,
+            commands::keyring_get,
+            commands::keyring_delete,
+            commands::compare_versions,
+            commands::version_satisfies,
+            commands::get_pending_updates,
+            commands::check_portal_permission,
+            commands::get_wm_capabilities,
+            commands::window_feature_supported,
+            commands::clipboard_available_formats,
+            commands::clipboard_read_files,
+            commands::clipboard_write_files,
+            commands::watch_clipboard,
+            commands::unwatch_clipboard,
+            commands::get_firewall_status,
+            commands::set_firewall_enabled,
+            commands::fs_batch,
+            commands::get_audio_activity,
+            commands::open_in_editor,
+            commands::list_terminals,
+            commands::get_default_terminal,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                app.state::<commands::RunningStreams>().shutdown();
+                app.state::<commands::InteractiveSessions>().shutdown();
+                app.state::<commands::RuntimeSockets>().cleanup();
+            }
+        });
 }
@@ -13,12 +13,18 @@ use thiserror::Error;
 
 use tauri::WebviewWindow;
 
+pub mod events;
+
 #[derive(Debug, Error)]
 pub enum PlatformError {
     #[error("{0}")]
     Msg(String),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
 }
 
 #[derive(Debug, Clone, Copy, serde::Serialize)]
@@ -40,12 +46,293 @@ pub fn current_platform() -> Platform {
     { Platform::Unknown }
 }
 
+/// True when running inside a Wayland session.
+pub fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE").map(|t| t == "wayland").unwrap_or(false)
+}
+
+/// True when `$XDG_CURRENT_DESKTOP` mentions `name` (case-insensitive),
+/// e.g. `desktop_is("gnome")` or `desktop_is("kde")`.
+pub fn desktop_is(name: &str) -> bool {
+    std::env::var("XDG_CURRENT_DESKTOP")
+        .map(|d| d.to_lowercase().split(':').any(|part| part.contains(name)))
+        .unwrap_or(false)
+}
+
+/// `kreadconfig6`/`kwriteconfig6` on Plasma 6, else the `5` variants.
+#[cfg(target_os = "linux")]
+fn kde_config_tool(write: bool) -> &'static str {
+    match (write, find_in_path(if write { "kwriteconfig6" } else { "kreadconfig6" }).is_some()) {
+        (false, true) => "kreadconfig6",
+        (false, false) => "kreadconfig5",
+        (true, true) => "kwriteconfig6",
+        (true, false) => "kwriteconfig5",
+    }
+}
+
+/// Distribution name from `/etc/os-release` (`PRETTY_NAME`).
+pub fn distro_name() -> Result<String, PlatformError> {
+    let raw = std::fs::read_to_string("/etc/os-release")
+        .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))?;
+    raw.lines()
+        .find_map(|l| l.strip_prefix("PRETTY_NAME="))
+        .map(|v| v.trim_matches('"').to_string())
+        .ok_or_else(|| PlatformError::Msg("os-release has no PRETTY_NAME".into()))
+}
+
+/// Running kernel release, e.g. "6.8.0-45-generic".
+pub fn kernel_version() -> Result<String, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(std::fs::read_to_string("/proc/sys/kernel/osrelease")?.trim().to_string())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        run_tool("uname", &["-r"])
+    }
+}
+
+/// "flatpak", "snap", "appimage", or `None` when not sandboxed/packaged.
+pub fn sandbox_kind() -> Option<&'static str> {
+    if std::path::Path::new("/.flatpak-info").exists() {
+        Some("flatpak")
+    } else if std::env::var_os("SNAP").is_some() {
+        Some("snap")
+    } else if std::env::var_os("APPIMAGE").is_some() {
+        Some("appimage")
+    } else {
+        None
+    }
+}
+
+/// Total physical memory in bytes (`MemTotal` from `/proc/meminfo`).
+pub fn total_memory_bytes() -> Result<u64, PlatformError> {
+    let raw = std::fs::read_to_string("/proc/meminfo")?;
+    raw.lines()
+        .find_map(|l| l.strip_prefix("MemTotal:"))
+        .and_then(|v| v.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+        .ok_or_else(|| PlatformError::Msg("meminfo has no MemTotal".into()))
+}
+
 /// Open a URL or file using the system default app (cross-platform).
 pub fn open_system(url_or_path: &str) -> Result<(), PlatformError> {
     open::that(url_or_path)
         .map_err(|e| PlatformError::Msg(format!("Failed to open '{}': {e}", url_or_path)))
 }
 
+/// Per-user runtime directory for sockets and locks: `$XDG_RUNTIME_DIR`, or a
+/// private `/tmp/<identifier>-<uid>` directory (mode 0700) when it's unset.
+pub fn runtime_dir(identifier: &str) -> Result<PathBuf, PlatformError> {
+    if let Some(dir) = dirs::runtime_dir() {
+        return Ok(dir);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+        // SAFETY: getuid has no preconditions.
+        let uid = unsafe { libc::getuid() };
+        let dir = std::env::temp_dir().join(format!("{identifier}-{uid}"));
+        match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(PlatformError::Io(e)),
+        }
+        // Refuse a pre-existing directory someone else planted or left open.
+        let meta = std::fs::symlink_metadata(&dir)?;
+        if !meta.is_dir() || meta.uid() != uid || meta.permissions().mode() & 0o077 != 0 {
+            return Err(PlatformError::Msg(format!(
+                "Insecure runtime directory '{}'",
+                dir.display()
+            )));
+        }
+        Ok(dir)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = identifier;
+        Err(PlatformError::Unsupported("no runtime directory on this platform".into()))
+    }
+}
+
+/// Bind a Unix socket named `name` under `<runtime dir>/<identifier>/`,
+/// replacing a stale socket file left by a previous run.
+#[cfg(unix)]
+pub fn create_runtime_socket(
+    identifier: &str,
+    name: &str,
+) -> Result<(PathBuf, std::os::unix::net::UnixListener), PlatformError> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(PlatformError::Msg(format!("Invalid socket name '{name}'")));
+    }
+    let dir = runtime_dir(identifier)?.join(identifier);
+    std::fs::DirBuilder::new().recursive(true).mode(0o700).create(&dir)?;
+    let path = dir.join(format!("{name}.sock"));
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    let listener = std::os::unix::net::UnixListener::bind(&path)
+        .map_err(|e| PlatformError::Msg(format!("Failed to bind '{}': {e}", path.display())))?;
+    Ok((path, listener))
+}
+
+/// Hand files to the desktop through the XDG `OpenURI` portal's `OpenFile`
+/// with `ask` set, so the portal's app chooser appears for each file. The
+/// portal only accepts local files as an fd, which is passed through
+/// `gdbus` (GLib 2.68+ forwards `h` arguments as file descriptors).
+pub fn share_files(paths: &[String]) -> Result<(), PlatformError> {
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let file = std::fs::File::open(path)
+            .map_err(|e| PlatformError::Msg(format!("Cannot open '{path}': {e}")))?;
+        files.push(file);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::process::CommandExt;
+
+        for file in &files {
+            let fd = file.as_raw_fd();
+            let mut cmd = Command::new("gdbus");
+            cmd.args([
+                "call", "--session",
+                "--dest", "org.freedesktop.portal.Desktop",
+                "--object-path", "/org/freedesktop/portal/desktop",
+                "--method", "org.freedesktop.portal.OpenURI.OpenFile",
+                "", &format!("@h {fd}"), "{'ask': <true>}",
+            ]);
+            // SAFETY: fcntl is async-signal-safe; this only lets gdbus
+            // inherit the fd it is told to forward.
+            unsafe {
+                cmd.pre_exec(move || {
+                    if libc::fcntl(fd, libc::F_SETFD, 0) == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+            let output = cmd.output().map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    PlatformError::Unsupported("'gdbus' is not installed".into())
+                } else {
+                    PlatformError::Io(e)
+                }
+            })?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                return Err(
+                    if stderr.contains("org.freedesktop.DBus.Error.ServiceUnknown")
+                        || stderr.contains("org.freedesktop.DBus.Error.NameHasNoOwner")
+                    {
+                        PlatformError::Unsupported("no xdg-desktop-portal backend is available".into())
+                    } else {
+                        PlatformError::Msg(format!("OpenFile portal call failed: {stderr}"))
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = files;
+        Err(PlatformError::Unsupported("the OpenURI portal is only available on Linux".into()))
+    }
+}
+
+/// Locate an executable on `$PATH`.
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(name);
+        #[cfg(target_os = "windows")]
+        let candidate = if candidate.extension().is_none() {
+            candidate.with_extension("exe")
+        } else {
+            candidate
+        };
+        candidate.is_file().then_some(candidate)
+    })
+}
+
+/// Known browsers: (name, Linux executables, macOS app name, Windows `start` target).
+const BROWSERS: &[(&str, &[&str], &str, &str)] = &[
+    ("firefox", &["firefox", "firefox-esr"], "Firefox", "firefox"),
+    ("chromium", &["chromium", "chromium-browser"], "Chromium", "chromium"),
+    ("chrome", &["google-chrome", "google-chrome-stable"], "Google Chrome", "chrome"),
+    ("brave", &["brave-browser", "brave"], "Brave Browser", "brave"),
+    ("edge", &["microsoft-edge", "microsoft-edge-stable"], "Microsoft Edge", "msedge"),
+    ("vivaldi", &["vivaldi", "vivaldi-stable"], "Vivaldi", "vivaldi"),
+    ("opera", &["opera"], "Opera", "opera"),
+    ("epiphany", &["epiphany", "epiphany-browser"], "", ""),
+    ("safari", &[], "Safari", ""),
+];
+
+/// Open an http(s) URL in a specific browser rather than the default one.
+pub fn open_url_in(url: &str, browser: &str) -> Result<(), PlatformError> {
+    let parsed = tauri::Url::parse(url)
+        .map_err(|e| PlatformError::Msg(format!("Invalid URL '{url}': {e}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(PlatformError::Msg(format!("Refusing to open non-http(s) URL '{url}'")));
+    }
+
+    let key = browser.trim().to_lowercase();
+    let &(_, executables, mac_app, win_target) = BROWSERS
+        .iter()
+        .find(|(name, ..)| *name == key)
+        .ok_or_else(|| PlatformError::Msg(format!("Unknown browser '{browser}'")))?;
+    let not_installed = || PlatformError::Msg(format!("Browser '{browser}' is not installed"));
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = executables;
+        let _ = win_target;
+        if mac_app.is_empty() {
+            return Err(not_installed());
+        }
+        let status = Command::new("open").args(["-a", mac_app, url]).status()?;
+        if !status.success() {
+            return Err(not_installed());
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = executables;
+        let _ = mac_app;
+        if win_target.is_empty() {
+            return Err(not_installed());
+        }
+        let status = Command::new("cmd").args(["/C", "start", "", win_target, url]).status()?;
+        if !status.success() {
+            return Err(not_installed());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = (mac_app, win_target);
+        let exe = executables
+            .iter()
+            .find_map(|name| find_in_path(name))
+            .ok_or_else(not_installed)?;
+        Command::new(exe).arg(url).spawn()?;
+        Ok(())
+    }
+}
+
 /// Return a per-app config directory.
 pub fn app_config_dir(identifier: &str) -> Result<PathBuf, PlatformError> {
     let base = dirs::config_dir()
@@ -60,62 +347,6267 @@ pub fn app_data_dir(identifier: &str) -> Result<PathBuf, PlatformError> {
     Ok(base.join(identifier))
 }
 
-/// Spawn a shell command non-blocking.
-pub fn spawn_shell(command: &str) -> Result<(), PlatformError> {
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WalkEntry {
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WalkResult {
+    pub entries: Vec<WalkEntry>,
+    /// Set when `limit` was hit before the walk finished.
+    pub truncated: bool,
+}
+
+/// Walk a directory tree iteratively up to `max_depth` (children of `root`
+/// are depth 1). Symlinked directories are only descended when
+/// `follow_symlinks` is set, and each directory is visited once by its
+/// canonical path so link cycles terminate.
+pub fn walk_dir(
+    root: &str,
+    max_depth: usize,
+    include_hidden: bool,
+    follow_symlinks: bool,
+    limit: usize,
+) -> Result<WalkResult, PlatformError> {
+    let root = PathBuf::from(root);
+    if !root.is_dir() {
+        return Err(PlatformError::Msg(format!("Not a directory: '{}'", root.display())));
+    }
+
+    let mut visited = std::collections::HashSet::new();
+    visited.insert(std::fs::canonicalize(&root)?);
+
+    let mut entries = Vec::new();
+    let mut stack = vec![(root, 0usize)];
+
+    while let Some((dir, depth)) = stack.pop() {
+        // Unreadable subdirectories are skipped rather than failing the walk.
+        let Ok(read_dir) = std::fs::read_dir(&dir) else { continue };
+        for entry in read_dir.flatten() {
+            let name = entry.file_name();
+            if !include_hidden && name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+            if entries.len() >= limit {
+                return Ok(WalkResult { entries, truncated: true });
+            }
+
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else { continue };
+            let is_symlink = file_type.is_symlink();
+            let meta = if is_symlink && follow_symlinks {
+                std::fs::metadata(&path).ok()
+            } else {
+                entry.metadata().ok()
+            };
+            let is_dir = meta.as_ref().map(|m| m.is_dir()).unwrap_or(false);
+            let size = meta.as_ref().map(|m| if m.is_dir() { 0 } else { m.len() }).unwrap_or(0);
+
+            entries.push(WalkEntry {
+                path: path.display().to_string(),
+                is_dir,
+                size,
+                depth: depth + 1,
+            });
+
+            let descend = is_dir && depth + 1 < max_depth && (!is_symlink || follow_symlinks);
+            if descend {
+                if let Ok(canonical) = std::fs::canonicalize(&path) {
+                    if visited.insert(canonical) {
+                        stack.push((path, depth + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(WalkResult { entries, truncated: false })
+}
+
+/// Expand a glob pattern (`**` recurses) into absolute paths. Relative
+/// patterns are resolved against the current directory; unreadable entries
+/// are skipped.
+pub fn expand_glob(pattern: &str, case_sensitive: bool) -> Result<Vec<String>, PlatformError> {
+    let pattern = if std::path::Path::new(pattern).is_absolute() {
+        pattern.to_string()
+    } else {
+        std::env::current_dir()?.join(pattern).display().to_string()
+    };
+    let options = glob::MatchOptions {
+        case_sensitive,
+        ..Default::default()
+    };
+    let paths = glob::glob_with(&pattern, options)
+        .map_err(|e| PlatformError::Msg(format!("Invalid glob pattern '{pattern}': {e}")))?;
+    Ok(paths.flatten().map(|p| p.display().to_string()).collect())
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PathStat {
+    pub path: String,
+    pub exists: bool,
+    /// Milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+    pub size: Option<u64>,
+}
+
+fn stat_path(path: &str) -> PathStat {
+    match std::fs::metadata(path) {
+        Ok(meta) => PathStat {
+            path: path.to_string(),
+            exists: true,
+            modified_ms: meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64),
+            size: Some(meta.len()),
+        },
+        Err(_) => PathStat { path: path.to_string(), exists: false, modified_ms: None, size: None },
+    }
+}
+
+/// Metadata for many paths at once, in input order. Missing or unreadable
+/// paths come back with `exists: false`. Large batches are split across
+/// threads (one per core).
+pub fn stat_many(paths: &[String]) -> Vec<PathStat> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if paths.len() < 64 || threads == 1 {
+        return paths.iter().map(|p| stat_path(p)).collect();
+    }
+    let chunk = paths.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk)
+            .map(|part| scope.spawn(move || part.iter().map(|p| stat_path(p)).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap_or_default()).collect()
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChunkedRead {
+    pub bytes_read: u64,
+    /// Size when the read started and when it finished
+    pub initial_size: u64,
+    pub final_size: u64,
+    pub cancelled: bool,
+}
+
+/// Read `path` from `offset` in `chunk_size` pieces, handing each to
+/// `on_chunk(offset, bytes)` until EOF or `cancel` is set. Reads go to the
+/// live end of file, so a file growing mid-read is followed and one that
+/// shrinks simply ends early; compare `initial_size`/`final_size` to tell.
+pub fn read_file_chunks(
+    path: &str,
+    offset: u64,
+    chunk_size: usize,
+    cancel: &std::sync::atomic::AtomicBool,
+    mut on_chunk: impl FnMut(u64, &[u8]),
+) -> Result<ChunkedRead, PlatformError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    let initial_size = file.metadata()?.len();
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut buf = vec![0u8; chunk_size.clamp(1, 16 * 1024 * 1024)];
+    let mut position = offset;
+    let mut cancelled = false;
+    loop {
+        if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+            cancelled = true;
+            break;
+        }
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        on_chunk(position, &buf[..n]);
+        position += n as u64;
+    }
+
+    Ok(ChunkedRead {
+        bytes_read: position - offset,
+        initial_size,
+        final_size: file.metadata().map(|m| m.len()).unwrap_or(position),
+        cancelled,
+    })
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExecutableInfo {
+    /// "elf" | "script" | "unknown"
+    pub kind: String,
+    /// Shebang interpreter line for scripts, e.g. "/usr/bin/env python3"
+    pub interpreter: Option<String>,
+    /// ELF machine, e.g. "x86_64", "aarch64"
+    pub arch: Option<String>,
+}
+
+/// Identify an executable from its first bytes: a `#!` script or an ELF binary.
+pub fn inspect_executable(path: &str) -> Result<ExecutableInfo, PlatformError> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => PlatformError::Msg(format!("Not found: '{path}'")),
+        _ => PlatformError::Io(e),
+    })?;
+    let mut head = [0u8; 256];
+    let mut len = 0;
+    while len < head.len() {
+        match file.read(&mut head[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    let head = &head[..len];
+
+    if head.starts_with(b"#!") {
+        let line_end = head.iter().position(|&b| b == b'\n').unwrap_or(head.len());
+        let interpreter = String::from_utf8_lossy(&head[2..line_end]).trim().to_string();
+        return Ok(ExecutableInfo {
+            kind: "script".into(),
+            interpreter: (!interpreter.is_empty()).then_some(interpreter),
+            arch: None,
+        });
+    }
+
+    if head.len() >= 20 && head.starts_with(b"\x7fELF") {
+        let raw = [head[18], head[19]];
+        // EI_DATA: 1 = little endian, 2 = big endian
+        let machine = if head[5] == 2 { u16::from_be_bytes(raw) } else { u16::from_le_bytes(raw) };
+        let arch = match machine {
+            0x03 => "x86".to_string(),
+            0x08 => "mips".to_string(),
+            0x14 => "powerpc".to_string(),
+            0x15 => "powerpc64".to_string(),
+            0x16 => "s390".to_string(),
+            0x28 => "arm".to_string(),
+            0x3E => "x86_64".to_string(),
+            0xB7 => "aarch64".to_string(),
+            0xF3 => "riscv".to_string(),
+            0x102 => "loongarch".to_string(),
+            other => format!("unknown(0x{other:x})"),
+        };
+        return Ok(ExecutableInfo { kind: "elf".into(), interpreter: None, arch: Some(arch) });
+    }
+
+    Ok(ExecutableInfo { kind: "unknown".into(), interpreter: None, arch: None })
+}
+
+/// Check that filesystem notifications work under `dir` (inotify can fail
+/// silently in some containers): create a probe file, time how long the
+/// watcher takes to report it, then remove it. Errors after 5 s of silence.
+pub fn watch_self_test(dir: &str) -> Result<f64, PlatformError> {
+    use ::notify::{RecursiveMode, Watcher};
+
+    let dir = std::fs::canonicalize(dir)
+        .map_err(|_| PlatformError::Msg(format!("No such directory: '{dir}'")))?;
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = ::notify::recommended_watcher(move |res: ::notify::Result<::notify::Event>| {
+        let _ = tx.send(res);
+    })
+    .map_err(|e| PlatformError::Msg(format!("Failed to create watcher: {e}")))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| PlatformError::Msg(format!("Failed to watch '{}': {e}", dir.display())))?;
+
+    let probe = dir.join(format!(".quack-watch-test-{}", std::process::id()));
+    let started = std::time::Instant::now();
+    std::fs::write(&probe, b"")?;
+
+    let timeout = std::time::Duration::from_secs(5);
+    let result = loop {
+        let remaining = timeout.saturating_sub(started.elapsed());
+        match rx.recv_timeout(remaining) {
+            Ok(Ok(event)) if event.paths.iter().any(|p| p == &probe) => {
+                break Ok(started.elapsed().as_secs_f64() * 1000.0);
+            }
+            Ok(_) => continue,
+            Err(_) => {
+                break Err(PlatformError::Msg(format!(
+                    "No change event within {}s; file watching is not working in '{}'",
+                    timeout.as_secs(),
+                    dir.display()
+                )))
+            }
+        }
+    };
+    drop(watcher);
+    let _ = std::fs::remove_file(&probe);
+    result
+}
+
+/// Trash
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TrashItem {
+    /// Name inside the trash's `files/` dir; pass to `restore_from_trash`
+    pub name: String,
+    pub original_path: String,
+    pub trashed_at_ms: Option<u64>,
+    pub size: u64,
+}
+
+/// Decode `%XX` escapes (as used in `.trashinfo` paths).
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(b) = u8::from_str_radix(hex, 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Total size of a file or directory tree (symlinks are not followed).
+fn disk_size(path: &std::path::Path) -> u64 {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| disk_size(&e.path())).sum())
+            .unwrap_or(0),
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Parse a `.trashinfo` `DeletionDate` (local time, `YYYY-MM-DDThh:mm:ss`).
+#[cfg(unix)]
+fn parse_deletion_date(raw: &str) -> Option<u64> {
+    let (date, time) = raw.trim().split_once('T')?;
+    let d: Vec<i32> = date.split('-').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let t: Vec<i32> = time.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    if d.len() != 3 || t.len() != 3 {
+        return None;
+    }
+    // SAFETY: tm is fully initialised (zeroed) before mktime reads it.
+    let secs = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        tm.tm_year = d[0] - 1900;
+        tm.tm_mon = d[1] - 1;
+        tm.tm_mday = d[2];
+        tm.tm_hour = t[0];
+        tm.tm_min = t[1];
+        tm.tm_sec = t[2];
+        tm.tm_isdst = -1;
+        libc::mktime(&mut tm)
+    };
+    (secs >= 0).then(|| secs as u64 * 1000)
+}
+
+#[cfg(not(unix))]
+fn parse_deletion_date(_raw: &str) -> Option<u64> {
+    None
+}
+
+/// The user's XDG trash directory (`$XDG_DATA_HOME/Trash`).
+fn trash_dir() -> Result<PathBuf, PlatformError> {
+    dirs::data_dir()
+        .map(|d| d.join("Trash"))
+        .ok_or_else(|| PlatformError::Msg("Could not resolve data directory".into()))
+}
+
+/// Items in the user's home trash, read from `files/` + `info/`.
+pub fn list_trash() -> Result<Vec<TrashItem>, PlatformError> {
+    let trash = trash_dir()?;
+    let Ok(infos) = std::fs::read_dir(trash.join("info")) else { return Ok(Vec::new()) };
+    let mut items = Vec::new();
+    for info in infos.flatten() {
+        let info_name = info.file_name().to_string_lossy().into_owned();
+        let Some(name) = info_name.strip_suffix(".trashinfo") else { continue };
+        let Ok(contents) = std::fs::read_to_string(info.path()) else { continue };
+        let field = |key: &str| contents.lines().find_map(|l| l.strip_prefix(key)).map(str::trim);
+        let Some(path) = field("Path=") else { continue };
+        let file = trash.join("files").join(name);
+        if std::fs::symlink_metadata(&file).is_err() {
+            continue;
+        }
+        items.push(TrashItem {
+            name: name.to_string(),
+            original_path: percent_decode(path),
+            trashed_at_ms: field("DeletionDate=").and_then(parse_deletion_date),
+            size: disk_size(&file),
+        });
+    }
+    Ok(items)
+}
+
+/// Move a trashed item back to its original location and drop its
+/// `.trashinfo`. If something now occupies that path, either fail or (with
+/// `rename_on_conflict`) restore next to it as "name (restored N).ext".
+/// Returns the path the item was restored to.
+pub fn restore_from_trash(name: &str, rename_on_conflict: bool) -> Result<String, PlatformError> {
+    if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+        return Err(PlatformError::Msg(format!("Invalid trash item '{name}'")));
+    }
+    let trash = trash_dir()?;
+    let info = trash.join("info").join(format!("{name}.trashinfo"));
+    let file = trash.join("files").join(name);
+    let contents = std::fs::read_to_string(&info)
+        .map_err(|_| PlatformError::Msg(format!("No trash item named '{name}'")))?;
+    let original = contents
+        .lines()
+        .find_map(|l| l.strip_prefix("Path="))
+        .map(|p| PathBuf::from(percent_decode(p.trim())))
+        .ok_or_else(|| PlatformError::Msg(format!("Corrupt trash info for '{name}'")))?;
+
+    let mut target = original.clone();
+    if std::fs::symlink_metadata(&target).is_ok() {
+        if !rename_on_conflict {
+            return Err(PlatformError::Msg(format!("'{}' already exists", original.display())));
+        }
+        let stem = original.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let ext = original.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+        let mut n = 1;
+        while std::fs::symlink_metadata(&target).is_ok() {
+            let suffix = if n == 1 { String::new() } else { format!(" {n}") };
+            target = original.with_file_name(format!("{stem} (restored{suffix}){ext}"));
+            n += 1;
+        }
+    }
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&file, &target)
+        .map_err(|e| PlatformError::Msg(format!("Failed to restore '{name}': {e}")))?;
+    let _ = std::fs::remove_file(&info);
+    Ok(target.display().to_string())
+}
+
+/// Extra knobs for `spawn_shell` / `run_shell_capture`.
+#[derive(Debug, Clone, Default)]
+pub struct ShellOptions {
+    /// Append stdout to this file instead of capturing it.
+    pub stdout_file: Option<PathBuf>,
+    /// Append stderr to this file instead of capturing it.
+    pub stderr_file: Option<PathBuf>,
+    /// Cap the child's memory: a transient systemd scope (`MemoryMax`) when
+    /// a user systemd instance is reachable, else `RLIMIT_AS`.
+    pub mem_limit_mb: Option<u64>,
+    /// Working directory; must exist.
+    pub cwd: Option<PathBuf>,
+    /// Variables added on top of the inherited environment.
+    pub env: Vec<(String, String)>,
+    /// Kill the command (and its children) after this long. Honored by
+    /// `spawn_shell` and the `run_shell_*` helpers.
+    pub timeout: Option<std::time::Duration>,
+    /// Fail on non-UTF-8 output instead of substituting U+FFFD.
+    pub strict_utf8: bool,
+}
+
+/// Result of `run_shell_capture`.
+#[derive(Debug, Clone, Default)]
+pub struct ShellOutput {
+    pub status: i32,
+    pub stdout: String,
+    pub stderr: String,
+    /// Whether the memory limit killed the command, as reported by its
+    /// systemd scope. `None` when the limit fell back to `RLIMIT_AS`, where
+    /// exceeding it fails allocations instead of killing the process.
+    pub oom_killed: Option<bool>,
+    /// The deadline passed and the process group was killed.
+    pub timed_out: bool,
+}
+
+/// Whether `systemd-run --user --scope` can be used for resource limits.
+#[cfg(target_os = "linux")]
+fn systemd_scope_available() -> bool {
+    find_in_path("systemd-run").is_some() && std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+}
+
+/// Whether the systemd scope `unit` ended because its cgroup hit the
+/// memory limit (systemd records `Result=oom-kill` from the cgroup's
+/// `memory.events`). Clears the failed unit afterwards, since scopes are
+/// started without `--collect` so the result can still be read.
+#[cfg(target_os = "linux")]
+fn scope_oom_killed(unit: &str) -> bool {
+    let result = run_tool("systemctl", &["--user", "show", "-p", "Result", "--value", unit]);
+    let _ = run_tool("systemctl", &["--user", "reset-failed", unit]);
+    result.map(|r| r == "oom-kill").unwrap_or(false)
+}
+
+/// Whether a memory-limited command was killed for exceeding the limit.
+/// Only a systemd scope can tell; under `RLIMIT_AS` the answer is unknown,
+/// and a SIGKILL there may just as well come from the global OOM killer.
+fn killed_by_memory_limit(opts: &ShellOptions, scope: Option<&str>) -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    if let Some(unit) = scope {
+        return Some(scope_oom_killed(unit));
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = scope;
+    if opts.mem_limit_mb.is_some() { None } else { Some(false) }
+}
+
+/// Numbers the transient scopes started for memory-limited commands.
+#[cfg(target_os = "linux")]
+static SCOPE_SEQ: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Open a log file for appending, creating parent directories as needed.
+fn open_log_file(path: &std::path::Path) -> Result<std::fs::File, PlatformError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| PlatformError::Msg(format!("Failed to open '{}': {e}", path.display())))
+}
+
+/// Build the platform shell invocation for `command` with `opts` applied.
+fn shell_command(command: &str, opts: &ShellOptions) -> Result<Command, PlatformError> {
+    shell_command_scoped(command, opts).map(|(cmd, _)| cmd)
+}
+
+/// `shell_command`, also returning the name of the systemd scope the
+/// command runs in when its memory limit is enforced that way.
+fn shell_command_scoped(command: &str, opts: &ShellOptions) -> Result<(Command, Option<String>), PlatformError> {
+    #[cfg(target_os = "linux")]
+    let scope_unit = (opts.mem_limit_mb.is_some() && systemd_scope_available()).then(|| {
+        let seq = SCOPE_SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        format!("quack-cmd-{}-{seq}.scope", std::process::id())
+    });
+    #[cfg(not(target_os = "linux"))]
+    let scope_unit: Option<String> = None;
+
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut c = Command::new("cmd");
+        c.args(["/C", command]);
+        c
+    };
+
     #[cfg(target_os = "windows")]
     {
-        Command::new("cmd").args(["/C", command]).spawn()?;
-        Ok(())
+        if opts.mem_limit_mb.is_some() {
+            return Err(PlatformError::Unsupported("memory limits are not available on Windows".into()));
+        }
     }
 
     #[cfg(any(target_os = "linux", target_os = "macos", not(target_os = "windows")))]
+    let mut cmd = {
+        let use_scope = scope_unit.is_some();
+
+        let mut c = if let Some(unit) = &scope_unit {
+            let mb = opts.mem_limit_mb.unwrap_or_default();
+            let mut c = Command::new("systemd-run");
+            c.args(["--user", "--scope", "--quiet", "--unit", unit])
+                .args(["-p", &format!("MemoryMax={mb}M"), "-p", "MemorySwapMax=0"])
+                .args(["--", "sh", "-c"])
+                .arg(command);
+            c
+        } else {
+            let mut c = Command::new("sh");
+            c.arg("-c").arg(command);
+            c
+        };
+
+        if let (Some(mb), false) = (opts.mem_limit_mb, use_scope) {
+            use std::os::unix::process::CommandExt;
+            let bytes = (mb.saturating_mul(1024 * 1024)) as libc::rlim_t;
+            // SAFETY: the closure only calls the async-signal-safe setrlimit.
+            unsafe {
+                c.pre_exec(move || {
+                    let limit = libc::rlimit { rlim_cur: bytes, rlim_max: bytes };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+        c
+    };
+
+    if let Some(dir) = &opts.cwd {
+        if !dir.is_dir() {
+            return Err(PlatformError::Msg(format!(
+                "Working directory '{}' does not exist or is not a directory",
+                dir.display()
+            )));
+        }
+        cmd.current_dir(dir);
+    }
+    cmd.envs(opts.env.iter().map(|(k, v)| (k, v)));
+
+    if let Some(path) = &opts.stdout_file {
+        cmd.stdout(std::process::Stdio::from(open_log_file(path)?));
+    }
+    if let Some(path) = &opts.stderr_file {
+        cmd.stderr(std::process::Stdio::from(open_log_file(path)?));
+    }
+    audit_command(command, opts)?;
+    Ok((cmd, scope_unit))
+}
+
+/// Atomically exchange two paths. Linux uses `renameat2(RENAME_EXCHANGE)`;
+/// elsewhere (or when the kernel/filesystem refuses) it falls back to a
+/// three-way rename through a temporary name next to `a`.
+pub fn swap_files(a: &str, b: &str) -> Result<(), PlatformError> {
+    let a = PathBuf::from(a);
+    let b = PathBuf::from(b);
+    for p in [&a, &b] {
+        if std::fs::symlink_metadata(p).is_err() {
+            return Err(PlatformError::Msg(format!("No such file: '{}'", p.display())));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
     {
-        Command::new("sh").arg("-c").arg(command).spawn()?;
-        Ok(())
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let ca = CString::new(a.as_os_str().as_bytes())
+            .map_err(|e| PlatformError::Msg(e.to_string()))?;
+        let cb = CString::new(b.as_os_str().as_bytes())
+            .map_err(|e| PlatformError::Msg(e.to_string()))?;
+        // SAFETY: both paths are valid NUL-terminated strings for the call's duration.
+        let rc = unsafe {
+            libc::syscall(
+                libc::SYS_renameat2,
+                libc::AT_FDCWD,
+                ca.as_ptr(),
+                libc::AT_FDCWD,
+                cb.as_ptr(),
+                libc::RENAME_EXCHANGE,
+            )
+        };
+        if rc == 0 {
+            return Ok(());
+        }
+        let err = std::io::Error::last_os_error();
+        match err.raw_os_error() {
+            // Not supported by this kernel or filesystem: use the fallback.
+            Some(libc::ENOSYS) | Some(libc::EINVAL) | Some(libc::EOPNOTSUPP) => {}
+            _ => return Err(PlatformError::Io(err)),
+        }
+    }
+
+    let file_name = a.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp = a.with_file_name(format!(".{file_name}.swap-{}", std::process::id()));
+    std::fs::rename(&a, &tmp)?;
+    if let Err(e) = std::fs::rename(&b, &a) {
+        let _ = std::fs::rename(&tmp, &a);
+        return Err(PlatformError::Msg(format!("swap failed: {e}")));
+    }
+    if let Err(e) = std::fs::rename(&tmp, &b) {
+        // Put things back the way they were.
+        let _ = std::fs::rename(&a, &b);
+        let _ = std::fs::rename(&tmp, &a);
+        return Err(PlatformError::Msg(format!("swap failed: {e}")));
     }
+    Ok(())
 }
 
-/// Run a command and capture stdout/stderr (blocking).
-pub fn run_shell_capture(command: &str) -> Result<(i32, String, String), PlatformError> {
-    #[cfg(target_os = "windows")]
-    let output = Command::new("cmd").args(["/C", command]).output()?;
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MountInfo {
+    pub source: String,
+    pub target: String,
+    pub fstype: String,
+    pub options: Vec<String>,
+    pub read_only: bool,
+    /// Mounts a subtree of another filesystem (bind mount).
+    pub bind: bool,
+}
 
-    #[cfg(any(target_os = "linux", target_os = "macos", not(target_os = "windows")))]
-    let output = Command::new("sh").arg("-c").arg(command).output()?;
+/// Undo the octal escapes (`\040` for space, …) used in mount tables.
+#[cfg(target_os = "linux")]
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            let digits = std::str::from_utf8(&bytes[i + 1..i + 4]).unwrap_or("");
+            if let Ok(code) = u8::from_str_radix(digits, 8) {
+                out.push(code);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
 
-    let code = output.status.code().unwrap_or(-1);
-    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-    Ok((code, stdout, stderr))
+/// List mounted filesystems. Linux parses `/proc/self/mountinfo`; macOS
+/// parses `mount` output.
+pub fn list_mounts() -> Result<Vec<MountInfo>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let raw = std::fs::read_to_string("/proc/self/mountinfo")?;
+        let mounts = raw
+            .lines()
+            .filter_map(|line| {
+                // id parent major:minor root target opts [optional…] - fstype source super-opts
+                let (head, tail) = line.split_once(" - ")?;
+                let head: Vec<&str> = head.split(' ').collect();
+                let tail: Vec<&str> = tail.split(' ').collect();
+                if head.len() < 6 || tail.len() < 3 {
+                    return None;
+                }
+                let mut options: Vec<String> = head[5].split(',').map(str::to_string).collect();
+                for opt in tail[2].split(',') {
+                    if !options.iter().any(|o| o == opt) {
+                        options.push(opt.to_string());
+                    }
+                }
+                Some(MountInfo {
+                    source: unescape_mount_field(tail[1]),
+                    target: unescape_mount_field(head[4]),
+                    fstype: tail[0].to_string(),
+                    read_only: options.iter().any(|o| o == "ro"),
+                    bind: head[3] != "/",
+                    options,
+                })
+            })
+            .collect();
+        Ok(mounts)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let raw = run_tool("mount", &[])?;
+        let mounts = raw
+            .lines()
+            .filter_map(|line| {
+                // "/dev/disk1s1 on / (apfs, local, journaled)"
+                let (source, rest) = line.split_once(" on ")?;
+                let (target, opts) = rest.rsplit_once(" (")?;
+                let mut opts = opts.trim_end_matches(')').split(", ").map(str::to_string);
+                let fstype = opts.next().unwrap_or_default();
+                let options: Vec<String> = opts.collect();
+                Some(MountInfo {
+                    source: source.to_string(),
+                    target: target.to_string(),
+                    fstype,
+                    read_only: options.iter().any(|o| o == "read-only"),
+                    bind: false,
+                    options,
+                })
+            })
+            .collect();
+        Ok(mounts)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Err(PlatformError::Unsupported("mount listing is not available on this platform".into()))
+    }
 }
 
-/// Window helpers (all via Tauri v2’s WebviewWindow API)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ShellList {
+    pub shells: Vec<String>,
+    pub login_shell: Option<String>,
+}
 
-pub fn set_always_on_top(window: &WebviewWindow, enabled: bool) -> Result<(), PlatformError> {
-    window.set_always_on_top(enabled)
-        .map_err(|e| PlatformError::Msg(format!("set_always_on_top failed: {e}")))
+/// Installed shells from `/etc/shells` (only those that exist) plus the
+/// user's login shell from the passwd database, or `$SHELL` as a fallback.
+pub fn list_shells() -> Result<ShellList, PlatformError> {
+    #[cfg(unix)]
+    {
+        let mut shells: Vec<String> = std::fs::read_to_string("/etc/shells")
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter(|l| std::path::Path::new(l).is_file())
+            .map(str::to_string)
+            .collect();
+        shells.dedup();
+        if shells.is_empty() {
+            shells.push("/bin/sh".to_string());
+        }
+
+        // SAFETY: getpwuid returns a pointer into static storage (or null);
+        // the shell string is copied out before any other passwd call.
+        let passwd_shell = unsafe {
+            let pw = libc::getpwuid(libc::getuid());
+            if pw.is_null() || (*pw).pw_shell.is_null() {
+                None
+            } else {
+                Some(std::ffi::CStr::from_ptr((*pw).pw_shell).to_string_lossy().into_owned())
+            }
+        };
+        let login_shell = passwd_shell
+            .filter(|s| !s.is_empty())
+            .or_else(|| std::env::var("SHELL").ok());
+        Ok(ShellList { shells, login_shell })
+    }
+
+    #[cfg(not(unix))]
+    {
+        Err(PlatformError::Unsupported("shell listing is only available on Unix".into()))
+    }
 }
 
-pub fn set_decorations(window: &WebviewWindow, enabled: bool) -> Result<(), PlatformError> {
-    window.set_decorations(enabled)
-        .map_err(|e| PlatformError::Msg(format!("set_decorations failed: {e}")))
+/// Spawn a shell command non-blocking.
+pub fn spawn_shell(command: &str, opts: &ShellOptions) -> Result<(), PlatformError> {
+    let Some(timeout) = opts.timeout else {
+        shell_command(command, opts)?.spawn()?;
+        return Ok(());
+    };
+
+    let mut child = spawn_shell_group(command, opts)?;
+    std::thread::spawn(move || {
+        let deadline = std::time::Instant::now() + timeout;
+        while let Ok(None) = child.try_wait() {
+            if std::time::Instant::now() >= deadline {
+                kill_process_tree(&mut child);
+                let _ = child.wait();
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+    });
+    Ok(())
 }
 
-pub fn set_shadow(window: &WebviewWindow, enabled: bool) -> Result<(), PlatformError> {
-    window.set_shadow(enabled)
-        .map_err(|e| PlatformError::Msg(format!("set_shadow failed: {e}")))
+/// Spawn a command as the leader of its own process group so it can later
+/// be killed together with its children (`kill_process_group`).
+pub fn spawn_shell_group(command: &str, opts: &ShellOptions) -> Result<std::process::Child, PlatformError> {
+    let mut cmd = shell_command(command, opts)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    Ok(cmd.spawn()?)
 }
 
-pub fn resize(window: &WebviewWindow, width: f64, height: f64) -> Result<(), PlatformError> {
-    use tauri::PhysicalSize;
-    window
-        .set_size(PhysicalSize::new(width, height))
-        .map_err(|e| PlatformError::Msg(format!("resize failed: {e}")))
+/// Run a command and capture stdout/stderr (blocking).
+/// Streams redirected to a file take precedence and come back empty.
+pub fn run_shell_capture(command: &str, opts: &ShellOptions) -> Result<ShellOutput, PlatformError> {
+    run_shell_until(command, opts, None)
 }
 
-pub fn move_window(window: &WebviewWindow, x: f64, y: f64) -> Result<(), PlatformError> {
-    use tauri::PhysicalPosition;
-    window
-        .set_position(PhysicalPosition::new(x, y))
-        .map_err(|e| PlatformError::Msg(format!("move failed: {e}")))
+/// Kill a child and, on Unix, every process in its process group.
+fn kill_process_tree(child: &mut std::process::Child) {
+    #[cfg(unix)]
+    {
+        // SAFETY: signalling a process group we created has no memory-safety concerns.
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+        }
+    }
+    let _ = child.kill();
+}
+
+/// Kill the process group led by `pid` (as created by the streaming and
+/// capturing helpers), taking any children it spawned down with it.
+pub fn kill_process_group(pid: u32) -> Result<(), PlatformError> {
+    #[cfg(unix)]
+    {
+        // SAFETY: kill(2) has no memory-safety preconditions.
+        if unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGKILL) } == -1 {
+            return Err(PlatformError::Io(std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    {
+        run_tool("taskkill", &["/T", "/F", "/PID", &pid.to_string()]).map(|_| ())
+    }
+}
+
+/// Compile a pattern for `named_captures`, rejecting invalid regexes.
+pub fn compile_pattern(pattern: &str) -> Result<regex::Regex, PlatformError> {
+    regex::Regex::new(pattern).map_err(|e| PlatformError::Msg(format!("Invalid pattern: {e}")))
+}
+
+/// The named groups of the first match of `re` in `text`, e.g.
+/// `(?P<file>[^:]+):(?P<line>\d+)` -> {"file": …, "line": …}. `None` when
+/// nothing matches; groups that didn't participate are left out.
+pub fn named_captures(re: &regex::Regex, text: &str) -> Option<std::collections::HashMap<String, String>> {
+    let caps = re.captures(text)?;
+    Some(
+        re.capture_names()
+            .flatten()
+            .filter_map(|name| caps.name(name).map(|m| (name.to_string(), m.as_str().to_string())))
+            .collect(),
+    )
+}
+
+/// Decode captured output, lossily or (with `strict`) failing with the
+/// position of the first invalid byte.
+fn decode_output(bytes: Vec<u8>, stream: &str, strict: bool) -> Result<String, PlatformError> {
+    if !strict {
+        return Ok(String::from_utf8_lossy(&bytes).into_owned());
+    }
+    String::from_utf8(bytes).map_err(|e| {
+        let offset = e.utf8_error().valid_up_to();
+        let before = &e.as_bytes()[..offset];
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+        let line_start = before.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        // Column in characters; everything before `offset` is valid UTF-8.
+        let column = String::from_utf8_lossy(&before[line_start..]).chars().count() + 1;
+        PlatformError::Msg(format!(
+            "{stream} is not valid UTF-8: invalid byte 0x{:02x} at offset {offset} (line {line}, column {column})",
+            e.as_bytes()[offset]
+        ))
+    })
+}
+
+/// Like `run_shell_capture`, but kills the command's process group once
+/// `deadline` passes and reports `timed_out`.
+pub fn run_shell_until(
+    command: &str,
+    opts: &ShellOptions,
+    deadline: Option<std::time::Instant>,
+) -> Result<ShellOutput, PlatformError> {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let deadline = match (deadline, opts.timeout) {
+        (Some(d), Some(t)) => Some(d.min(std::time::Instant::now() + t)),
+        (d, t) => d.or_else(|| t.map(|t| std::time::Instant::now() + t)),
+    };
+    let (mut cmd, scope_unit) = shell_command_scoped(command, opts)?;
+    cmd.stdin(Stdio::null());
+    if opts.stdout_file.is_none() {
+        cmd.stdout(Stdio::piped());
+    }
+    if opts.stderr_file.is_none() {
+        cmd.stderr(Stdio::piped());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    let mut child = cmd.spawn()?;
+    // Drain both pipes on their own threads so a chatty child can't block on a full pipe.
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        pipe.map(|mut p| {
+            std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = p.read_to_end(&mut buf);
+                buf
+            })
+        })
+    };
+    let out_reader = drain(child.stdout.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+    let err_reader = drain(child.stderr.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if deadline.is_some_and(|d| std::time::Instant::now() >= d) {
+            kill_process_tree(&mut child);
+            timed_out = true;
+            break child.wait()?;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+
+    let collect = |reader: Option<std::thread::JoinHandle<Vec<u8>>>| {
+        reader.and_then(|h| h.join().ok()).unwrap_or_default()
+    };
+    let stdout = decode_output(collect(out_reader), "stdout", opts.strict_utf8)?;
+    let stderr = decode_output(collect(err_reader), "stderr", opts.strict_utf8)?;
+    // Always ask, so a failed scope is reset even when the deadline killed it.
+    let oom_killed = killed_by_memory_limit(opts, scope_unit.as_deref());
+
+    Ok(ShellOutput {
+        status: status.code().unwrap_or(-1),
+        stdout,
+        stderr,
+        oom_killed: if timed_out { Some(false) } else { oom_killed },
+        timed_out,
+    })
+}
+
+/// A shell command whose output is being delivered line by line.
+pub struct StreamingChild {
+    child: std::process::Child,
+    readers: Vec<std::thread::JoinHandle<()>>,
+    tee: Option<std::sync::Arc<std::sync::Mutex<std::io::BufWriter<std::fs::File>>>>,
+}
+
+impl StreamingChild {
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Wait for the process to exit and both pipes to drain, then flush
+    /// the tee file. Returns the exit code (-1 when killed by a signal).
+    pub fn wait(mut self) -> Result<i32, PlatformError> {
+        use std::io::Write;
+
+        let status = self.child.wait()?;
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+        if let Some(tee) = &self.tee {
+            if let Ok(mut file) = tee.lock() {
+                file.flush()?;
+            }
+        }
+        Ok(status.code().unwrap_or(-1))
+    }
+}
+
+/// Spawn a shell command with piped stdout/stderr and call
+/// `on_line("stdout" | "stderr", line)` for every line from a reader thread.
+/// Non-UTF-8 bytes are decoded lossily. With `tee_file`, every line is also
+/// appended to that file (buffered; flushed by `StreamingChild::wait`).
+pub fn spawn_shell_streaming(
+    command: &str,
+    opts: &ShellOptions,
+    tee_file: Option<&std::path::Path>,
+    on_line: impl Fn(&str, &str) + Send + Sync + 'static,
+) -> Result<StreamingChild, PlatformError> {
+    use std::io::{BufRead, Write};
+    use std::process::Stdio;
+    use std::sync::{Arc, Mutex};
+
+    let mut cmd = shell_command(command, opts)?;
+    cmd.stdin(Stdio::null());
+    // Streams redirected to a file stay there and are not streamed.
+    if opts.stdout_file.is_none() {
+        cmd.stdout(Stdio::piped());
+    }
+    if opts.stderr_file.is_none() {
+        cmd.stderr(Stdio::piped());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    let tee = match tee_file {
+        Some(path) => Some(Arc::new(Mutex::new(std::io::BufWriter::new(open_log_file(path)?)))),
+        None => None,
+    };
+    let mut child = cmd.spawn()?;
+    let on_line = Arc::new(on_line);
+
+    let mut readers = Vec::new();
+    let pipes: [(&'static str, Option<Box<dyn std::io::Read + Send>>); 2] = [
+        ("stdout", child.stdout.take().map(|p| Box::new(p) as Box<dyn std::io::Read + Send>)),
+        ("stderr", child.stderr.take().map(|p| Box::new(p) as Box<dyn std::io::Read + Send>)),
+    ];
+    for (stream, pipe) in pipes {
+        let Some(pipe) = pipe else { continue };
+        let on_line = on_line.clone();
+        let tee = tee.clone();
+        readers.push(std::thread::spawn(move || {
+            let mut reader = std::io::BufReader::new(pipe);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+                if let Some(tee) = &tee {
+                    if let Ok(mut file) = tee.lock() {
+                        let _ = file.write_all(&buf);
+                    }
+                }
+                let line = String::from_utf8_lossy(&buf);
+                on_line(stream, line.trim_end_matches(['\n', '\r']));
+            }
+        }));
+    }
+
+    Ok(StreamingChild { child, readers, tee })
+}
+
+enum InteractiveProcess {
+    Piped(std::process::Child),
+    Pty {
+        child: Box<dyn portable_pty::Child + Send + Sync>,
+        // Kept open for the session; dropping it hangs up the terminal.
+        _master: Box<dyn portable_pty::MasterPty + Send>,
+    },
+}
+
+/// A command whose stdin is fed by the caller and whose output is
+/// delivered in chunks as it arrives.
+pub struct InteractiveChild {
+    stdin: Option<Box<dyn std::io::Write + Send>>,
+    process: InteractiveProcess,
+    readers: Vec<std::thread::JoinHandle<()>>,
+}
+
+impl InteractiveChild {
+    pub fn write(&mut self, data: &[u8]) -> Result<(), PlatformError> {
+        use std::io::Write;
+
+        let stdin = self.stdin.as_mut().ok_or_else(|| PlatformError::Msg("stdin is closed".into()))?;
+        stdin.write_all(data)?;
+        stdin.flush()?;
+        Ok(())
+    }
+
+    /// Send EOF, give the process `grace` to exit, then kill it and its
+    /// children. Joins the reader threads; returns the exit code (-1 when
+    /// killed by a signal).
+    pub fn close(mut self, grace: std::time::Duration) -> Result<i32, PlatformError> {
+        use portable_pty::ChildKiller;
+        use std::io::Write;
+
+        // Dropping stdin closes the pipe; a terminal additionally needs ^D.
+        if let Some(mut stdin) = self.stdin.take() {
+            if matches!(self.process, InteractiveProcess::Pty { .. }) {
+                let _ = stdin.write_all(b"\x04");
+            }
+        }
+        let deadline = std::time::Instant::now() + grace;
+        let code = loop {
+            let exited = match &mut self.process {
+                InteractiveProcess::Piped(child) => child.try_wait()?.map(|s| s.code().unwrap_or(-1)),
+                InteractiveProcess::Pty { child, .. } => child.try_wait()?.map(|s| s.exit_code() as i32),
+            };
+            if let Some(code) = exited {
+                break code;
+            }
+            if std::time::Instant::now() >= deadline {
+                match &mut self.process {
+                    InteractiveProcess::Piped(child) => {
+                        kill_process_tree(child);
+                        let _ = child.wait();
+                    }
+                    InteractiveProcess::Pty { child, .. } => {
+                        if let Some(pid) = child.process_id() {
+                            let _ = kill_process_group(pid);
+                        }
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                }
+                break -1;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        };
+        // Hang up the terminal so its reader sees EOF.
+        drop(self.process);
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+        Ok(code)
+    }
+}
+
+/// Spawn a shell command for interactive use. With `pty`, it runs on a
+/// pseudo-terminal (so shells and REPLs behave interactively) and all
+/// output arrives as "stdout"; memory limits and file redirects don't apply
+/// then. `on_output(stream, text)` gets chunks as they're read (split only
+/// on UTF-8 boundaries); `on_end` runs once every output stream has closed.
+pub fn spawn_interactive(
+    command: &str,
+    opts: &ShellOptions,
+    pty: bool,
+    on_output: impl Fn(&str, &str) + Send + Sync + 'static,
+    on_end: impl FnOnce() + Send + 'static,
+) -> Result<InteractiveChild, PlatformError> {
+    use std::io::Read;
+    use std::process::Stdio;
+    use std::sync::{Arc, Mutex};
+
+    type Pipe = Box<dyn std::io::Read + Send>;
+    fn pty_error(e: impl std::fmt::Display) -> PlatformError {
+        PlatformError::Msg(format!("pty: {e}"))
+    }
+
+    let (stdin, process, pipes): (Box<dyn std::io::Write + Send>, InteractiveProcess, Vec<(&'static str, Pipe)>) = if pty {
+        use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+
+        let pair = native_pty_system()
+            .openpty(PtySize { rows: 24, cols: 80, pixel_width: 0, pixel_height: 0 })
+            .map_err(pty_error)?;
+
+        #[cfg(target_os = "windows")]
+        let mut builder = {
+            let mut b = CommandBuilder::new("cmd");
+            b.args(["/C", command]);
+            b
+        };
+        #[cfg(not(target_os = "windows"))]
+        let mut builder = {
+            let mut b = CommandBuilder::new("sh");
+            b.args(["-c", command]);
+            b
+        };
+        builder.env("TERM", "xterm-256color");
+        for (key, value) in &opts.env {
+            builder.env(key, value);
+        }
+        match &opts.cwd {
+            Some(dir) if !dir.is_dir() => {
+                return Err(PlatformError::Msg(format!(
+                    "Working directory '{}' does not exist or is not a directory",
+                    dir.display()
+                )));
+            }
+            Some(dir) => builder.cwd(dir),
+            None => {
+                if let Ok(dir) = std::env::current_dir() {
+                    builder.cwd(dir);
+                }
+            }
+        }
+
+        audit_command(command, opts)?;
+        let child = pair.slave.spawn_command(builder).map_err(pty_error)?;
+        // Our copy of the slave must go, or the reader never sees EOF.
+        drop(pair.slave);
+        let reader = pair.master.try_clone_reader().map_err(pty_error)?;
+        let writer = pair.master.take_writer().map_err(pty_error)?;
+        (writer, InteractiveProcess::Pty { child, _master: pair.master }, vec![("stdout", reader)])
+    } else {
+        let mut cmd = shell_command(command, opts)?;
+        cmd.stdin(Stdio::piped());
+        if opts.stdout_file.is_none() {
+            cmd.stdout(Stdio::piped());
+        }
+        if opts.stderr_file.is_none() {
+            cmd.stderr(Stdio::piped());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        let mut child = cmd.spawn()?;
+        let stdin: Box<dyn std::io::Write + Send> = Box::new(child.stdin.take().expect("stdin is piped"));
+        let mut pipes: Vec<(&'static str, Pipe)> = Vec::new();
+        if let Some(out) = child.stdout.take() {
+            pipes.push(("stdout", Box::new(out)));
+        }
+        if let Some(err) = child.stderr.take() {
+            pipes.push(("stderr", Box::new(err)));
+        }
+        (stdin, InteractiveProcess::Piped(child), pipes)
+    };
+
+    let on_output = Arc::new(on_output);
+    let on_end = Arc::new(Mutex::new(Some(on_end)));
+    let remaining = Arc::new(std::sync::atomic::AtomicUsize::new(pipes.len()));
+    if pipes.is_empty() {
+        if let Some(end) = on_end.lock().ok().and_then(|mut e| e.take()) {
+            end();
+        }
+    }
+
+    let readers = pipes
+        .into_iter()
+        .map(|(stream, mut pipe)| {
+            let on_output = on_output.clone();
+            let on_end = on_end.clone();
+            let remaining = remaining.clone();
+            std::thread::spawn(move || {
+                let mut buf = [0u8; 8192];
+                let mut pending: Vec<u8> = Vec::new();
+                loop {
+                    let n = match pipe.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => n,
+                    };
+                    pending.extend_from_slice(&buf[..n]);
+                    // Hold back a multi-byte character split across reads.
+                    let valid = match std::str::from_utf8(&pending) {
+                        Ok(_) => pending.len(),
+                        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                        Err(_) => pending.len(),
+                    };
+                    if valid > 0 {
+                        on_output(stream, &String::from_utf8_lossy(&pending[..valid]));
+                        pending.drain(..valid);
+                    }
+                }
+                if !pending.is_empty() {
+                    on_output(stream, &String::from_utf8_lossy(&pending));
+                }
+                if remaining.fetch_sub(1, std::sync::atomic::Ordering::SeqCst) == 1 {
+                    if let Some(end) = on_end.lock().ok().and_then(|mut e| e.take()) {
+                        end();
+                    }
+                }
+            })
+        })
+        .collect();
+
+    Ok(InteractiveChild { stdin: Some(stdin), process, readers })
+}
+
+/// One program in a `run_pipeline` chain (no shell involved).
+#[derive(Debug, Clone)]
+pub struct PipelineStage {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PipelineOutput {
+    /// Exit code of the last stage
+    pub status: i32,
+    /// Last stage's stdout/stderr; earlier stages' stderr goes to ours
+    pub stdout: String,
+    pub stderr: String,
+    /// Every stage's exit code, like bash's `PIPESTATUS` (-1 = signal)
+    pub stage_statuses: Vec<i32>,
+}
+
+/// Run `a | b | c` with OS pipes between the stages, so arguments are
+/// never interpreted by a shell. `stdin` is fed to the first stage.
+pub fn run_pipeline(stages: &[PipelineStage], stdin: Option<&str>) -> Result<PipelineOutput, PlatformError> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    if stages.is_empty() {
+        return Err(PlatformError::Msg("pipeline has no stages".into()));
+    }
+
+    // Record every stage before starting any, so an audit failure leaves
+    // nothing half-run.
+    for stage in stages {
+        let line = std::iter::once(&stage.program)
+            .chain(&stage.args)
+            .map(|word| if word.contains(char::is_whitespace) { format!("'{word}'") } else { word.clone() })
+            .collect::<Vec<_>>()
+            .join(" ");
+        audit_command(&line, &ShellOptions::default())?;
+    }
+
+    let mut children: Vec<std::process::Child> = Vec::with_capacity(stages.len());
+    for (i, stage) in stages.iter().enumerate() {
+        let mut cmd = Command::new(&stage.program);
+        cmd.args(&stage.args);
+        let input = match children.last_mut() {
+            Some(prev) => prev.stdout.take().map(Stdio::from).unwrap_or_else(Stdio::null),
+            None if stdin.is_some() => Stdio::piped(),
+            None => Stdio::null(),
+        };
+        cmd.stdin(input).stdout(Stdio::piped());
+        if i + 1 == stages.len() {
+            cmd.stderr(Stdio::piped());
+        }
+        match cmd.spawn() {
+            Ok(child) => children.push(child),
+            Err(e) => {
+                for child in &mut children {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                return Err(PlatformError::Msg(format!("stage {i} ('{}') failed to start: {e}", stage.program)));
+            }
+        }
+    }
+
+    // Feed stdin from a thread so a stage that fills its output pipe
+    // before reading all input can't deadlock us.
+    let writer = match (stdin, children[0].stdin.take()) {
+        (Some(text), Some(mut pipe)) => {
+            let text = text.to_string();
+            Some(std::thread::spawn(move || {
+                let _ = pipe.write_all(text.as_bytes());
+            }))
+        }
+        _ => None,
+    };
+
+    let last = children.pop().expect("at least one stage");
+    let output = last.wait_with_output()?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+
+    let mut stage_statuses = Vec::with_capacity(stages.len());
+    for mut child in children {
+        stage_statuses.push(child.wait()?.code().unwrap_or(-1));
+    }
+    let status = output.status.code().unwrap_or(-1);
+    stage_statuses.push(status);
+
+    Ok(PipelineOutput {
+        status,
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        stage_statuses,
+    })
+}
+
+/// Retry a failing command until it succeeds, `max_attempts` is reached, or
+/// `budget` has elapsed in total; an attempt still running at the deadline
+/// is killed. Returns the last output, the number of attempts made, and
+/// whether retrying stopped because the budget ran out.
+pub fn run_shell_with_deadline(
+    command: &str,
+    opts: &ShellOptions,
+    budget: std::time::Duration,
+    max_attempts: u32,
+) -> Result<(ShellOutput, u32, bool), PlatformError> {
+    let deadline = std::time::Instant::now() + budget;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let out = run_shell_until(command, opts, Some(deadline))?;
+        if out.status == 0 {
+            return Ok((out, attempts, false));
+        }
+        if out.timed_out || std::time::Instant::now() >= deadline {
+            return Ok((out, attempts, true));
+        }
+        if attempts >= max_attempts.max(1) {
+            return Ok((out, attempts, false));
+        }
+    }
+}
+
+/// Parsed CSV: records keyed by header, or plain rows when headerless.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(untagged)]
+pub enum CsvRows {
+    Keyed(Vec<std::collections::HashMap<String, String>>),
+    Plain(Vec<Vec<String>>),
+}
+
+pub fn parse_csv(text: &str, has_header: bool, delimiter: char) -> Result<CsvRows, PlatformError> {
+    if !delimiter.is_ascii() {
+        return Err(PlatformError::Msg(format!("Delimiter must be ASCII, got '{delimiter}'")));
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(has_header)
+        .delimiter(delimiter as u8)
+        .from_reader(text.as_bytes());
+    let bad = |e: csv::Error| PlatformError::Msg(format!("CSV parse error: {e}"));
+
+    if has_header {
+        let headers = reader.headers().map_err(bad)?.clone();
+        let rows = reader
+            .records()
+            .map(|r| {
+                r.map(|record| {
+                    headers.iter().zip(record.iter()).map(|(h, v)| (h.to_string(), v.to_string())).collect()
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map_err(bad)?;
+        Ok(CsvRows::Keyed(rows))
+    } else {
+        let rows = reader
+            .records()
+            .map(|r| r.map(|record| record.iter().map(str::to_string).collect()))
+            .collect::<Result<_, _>>()
+            .map_err(bad)?;
+        Ok(CsvRows::Plain(rows))
+    }
+}
+
+/// Run a helper tool (gdbus, gsettings, …) and return its trimmed stdout.
+/// A missing binary maps to `Unsupported`; a non-zero exit to `Msg` with stderr.
+fn run_tool(program: &str, args: &[&str]) -> Result<String, PlatformError> {
+    let output = Command::new(program).args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            PlatformError::Unsupported(format!("'{program}' is not installed"))
+        } else {
+            PlatformError::Io(e)
+        }
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PlatformError::Msg(format!("{program} failed: {}", stderr.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Read a D-Bus property via `gdbus`, returning the GVariant text,
+/// e.g. `(<'balanced'>,)`.
+#[cfg(target_os = "linux")]
+fn dbus_get_property(
+    bus: &str,
+    dest: &str,
+    path: &str,
+    interface: &str,
+    property: &str,
+) -> Result<String, PlatformError> {
+    run_tool(
+        "gdbus",
+        &[
+            "call", bus, "--dest", dest, "--object-path", path,
+            "--method", "org.freedesktop.DBus.Properties.Get", interface, property,
+        ],
+    )
+}
+
+/// All single-quoted strings in a GVariant text dump, in order.
+#[cfg(target_os = "linux")]
+fn gvariant_strings(text: &str) -> Vec<String> {
+    text.split('\'').skip(1).step_by(2).map(str::to_string).collect()
+}
+
+/// Run a monitoring tool (`gdbus monitor`, `gsettings monitor`, …) on a
+/// background thread and call `on_line` with every line it prints, until
+/// `stop` is set. `stop` is also set when the tool exits, so callers can
+/// tell the watch has ended. The tool is spawned from that thread with
+/// `PR_SET_PDEATHSIG`, so it can't outlive the app.
+#[cfg(target_os = "linux")]
+fn watch_tool_lines(
+    program: &'static str,
+    args: Vec<String>,
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    mut on_line: impl FnMut(&str) + Send + 'static,
+) -> Result<(), PlatformError> {
+    use std::io::BufRead;
+    use std::os::unix::process::CommandExt;
+    use std::process::Stdio;
+    use std::sync::atomic::Ordering;
+    use std::sync::mpsc::RecvTimeoutError;
+
+    let (started_tx, started_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut cmd = Command::new(program);
+        cmd.args(&args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null());
+        // SAFETY: prctl is async-signal-safe. The death signal follows the
+        // thread that forked, which lives as long as the watch.
+        unsafe {
+            cmd.pre_exec(|| {
+                if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM as libc::c_ulong, 0, 0, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = started_tx.send(Err(e));
+                return;
+            }
+        };
+        let _ = started_tx.send(Ok(()));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            std::thread::spawn(move || {
+                for line in std::io::BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        while !stop.load(Ordering::Relaxed) {
+            match rx.recv_timeout(std::time::Duration::from_millis(200)) {
+                Ok(line) => on_line(&line),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        stop.store(true, Ordering::Relaxed);
+        let _ = child.kill();
+        let _ = child.wait();
+    });
+
+    match started_rx.recv() {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(PlatformError::Unsupported(format!("'{program}' is not installed")))
+        }
+        Ok(Err(e)) => Err(PlatformError::Io(e)),
+        Err(_) => Err(PlatformError::Msg(format!("{program} watcher exited before starting"))),
+    }
+}
+
+/// `file://` URI for a path, percent-encoding spaces and non-ASCII bytes.
+/// Relative paths are resolved against the current directory.
+pub fn path_to_uri(path: &str) -> Result<String, PlatformError> {
+    let path = std::path::Path::new(path);
+    let abs = if path.is_absolute() { path.to_path_buf() } else { std::env::current_dir()?.join(path) };
+    tauri::Url::from_file_path(&abs)
+        .map(|u| u.to_string())
+        .map_err(|_| PlatformError::Msg(format!("Cannot convert '{}' to a URI", abs.display())))
+}
+
+/// Filesystem path for a `file:` URI (`file:///p`, `file://localhost/p`, or
+/// `file://<this host>/p`). Other schemes and remote hosts are rejected.
+pub fn uri_to_path(uri: &str) -> Result<String, PlatformError> {
+    let mut url = tauri::Url::parse(uri.trim())
+        .map_err(|e| PlatformError::Msg(format!("Invalid URI '{uri}': {e}")))?;
+    if url.scheme() != "file" {
+        return Err(PlatformError::Msg(format!("Not a file URI: '{uri}'")));
+    }
+    if let Some(host) = url.host_str().map(str::to_string) {
+        let local = host == "localhost" || get_hostname().map(|h| h == host).unwrap_or(false);
+        if !local {
+            return Err(PlatformError::Msg(format!("URI points at remote host '{host}'")));
+        }
+        let _ = url.set_host(None);
+    }
+    url.to_file_path()
+        .map(|p| p.display().to_string())
+        .map_err(|_| PlatformError::Msg(format!("Cannot convert '{uri}' to a path")))
+}
+
+/// Express `to` relative to the directory `from` (e.g. `../assets/x.png`),
+/// purely lexically like `pathdiff`. Relative inputs are resolved against
+/// the current directory first; paths on different roots (another Windows
+/// drive) come back as the absolute `to`.
+pub fn relative_path(from: &str, to: &str) -> Result<String, PlatformError> {
+    use std::path::Component;
+
+    let absolutize = |p: &str| -> Result<PathBuf, PlatformError> {
+        let p = std::path::Path::new(p);
+        let abs = if p.is_absolute() { p.to_path_buf() } else { std::env::current_dir()?.join(p) };
+        // Resolve `.` and `..` lexically so the comparison is meaningful.
+        let mut out = PathBuf::new();
+        for c in abs.components() {
+            match c {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    out.pop();
+                }
+                other => out.push(other),
+            }
+        }
+        Ok(out)
+    };
+    let from = absolutize(from)?;
+    let to = absolutize(to)?;
+
+    let mut from_iter = from.components().peekable();
+    let mut to_iter = to.components().peekable();
+    if from_iter.peek() != to_iter.peek() {
+        return Ok(to.display().to_string());
+    }
+    while from_iter.peek().is_some() && from_iter.peek() == to_iter.peek() {
+        from_iter.next();
+        to_iter.next();
+    }
+
+    let mut rel = PathBuf::new();
+    for _ in from_iter {
+        rel.push("..");
+    }
+    for c in to_iter {
+        rel.push(c);
+    }
+    if rel.as_os_str().is_empty() {
+        rel.push(".");
+    }
+    Ok(rel.display().to_string())
+}
+
+/// Turn a dropped path into an absolute filesystem path. Some Wayland
+/// compositors hand over `file://` URIs instead of plain paths.
+pub fn normalize_dropped_path(raw: &std::path::Path) -> Option<PathBuf> {
+    let text = raw.to_string_lossy();
+    let path = if text.starts_with("file://") {
+        tauri::Url::parse(&text).ok()?.to_file_path().ok()?
+    } else {
+        raw.to_path_buf()
+    };
+    std::fs::canonicalize(path).ok()
+}
+
+/// Window helpers (all via Tauri v2’s WebviewWindow API)
+
+pub fn set_always_on_top(window: &WebviewWindow, enabled: bool) -> Result<(), PlatformError> {
+    window.set_always_on_top(enabled)
+        .map_err(|e| PlatformError::Msg(format!("set_always_on_top failed: {e}")))
+}
+
+/// Force a window to "dark" or "light", or follow the "system" preference;
+/// the webview's `prefers-color-scheme` follows. On Linux, GTK applies the
+/// preference to the whole application (`gtk-application-prefer-dark-theme`),
+/// so other windows change with it.
+pub fn set_window_theme(window: &WebviewWindow, theme: &str) -> Result<(), PlatformError> {
+    let theme = match theme {
+        "dark" => Some(tauri::Theme::Dark),
+        "light" => Some(tauri::Theme::Light),
+        "system" => None,
+        other => return Err(PlatformError::Msg(format!("Unknown theme '{other}', expected dark, light or system"))),
+    };
+    window.set_theme(theme)
+        .map_err(|e| PlatformError::Unsupported(format!("set_theme failed: {e}")))
+}
+
+/// Cursor names accepted by `set_cursor` (CSS `cursor` keywords).
+const CURSOR_NAMES: &[&str] = &[
+    "default", "pointer", "text", "wait", "progress", "crosshair", "move", "help",
+    "not-allowed", "grab", "grabbing", "col-resize", "row-resize", "ew-resize", "ns-resize",
+    "nesw-resize", "nwse-resize", "zoom-in", "zoom-out", "copy", "alias", "context-menu",
+];
+
+pub fn set_cursor(window: &WebviewWindow, name: &str) -> Result<(), PlatformError> {
+    use tauri::window::CursorIcon;
+
+    let icon = match name {
+        "default" => CursorIcon::Default,
+        "pointer" => CursorIcon::Hand,
+        "text" => CursorIcon::Text,
+        "wait" => CursorIcon::Wait,
+        "progress" => CursorIcon::Progress,
+        "crosshair" => CursorIcon::Crosshair,
+        "move" => CursorIcon::Move,
+        "help" => CursorIcon::Help,
+        "not-allowed" => CursorIcon::NotAllowed,
+        "grab" => CursorIcon::Grab,
+        "grabbing" => CursorIcon::Grabbing,
+        "col-resize" => CursorIcon::ColResize,
+        "row-resize" => CursorIcon::RowResize,
+        "ew-resize" => CursorIcon::EwResize,
+        "ns-resize" => CursorIcon::NsResize,
+        "nesw-resize" => CursorIcon::NeswResize,
+        "nwse-resize" => CursorIcon::NwseResize,
+        "zoom-in" => CursorIcon::ZoomIn,
+        "zoom-out" => CursorIcon::ZoomOut,
+        "copy" => CursorIcon::Copy,
+        "alias" => CursorIcon::Alias,
+        "context-menu" => CursorIcon::ContextMenu,
+        other => {
+            return Err(PlatformError::Msg(format!(
+                "Unknown cursor '{other}', expected one of: {}",
+                CURSOR_NAMES.join(", ")
+            )))
+        }
+    };
+    window.set_cursor_icon(icon)
+        .map_err(|e| PlatformError::Msg(format!("set_cursor failed: {e}")))
+}
+
+pub fn set_cursor_visible(window: &WebviewWindow, visible: bool) -> Result<(), PlatformError> {
+    window.set_cursor_visible(visible)
+        .map_err(|e| PlatformError::Msg(format!("set_cursor_visible failed: {e}")))
+}
+
+pub fn set_decorations(window: &WebviewWindow, enabled: bool) -> Result<(), PlatformError> {
+    window.set_decorations(enabled)
+        .map_err(|e| PlatformError::Msg(format!("set_decorations failed: {e}")))
+}
+
+pub fn set_shadow(window: &WebviewWindow, enabled: bool) -> Result<(), PlatformError> {
+    window.set_shadow(enabled)
+        .map_err(|e| PlatformError::Msg(format!("set_shadow failed: {e}")))
+}
+
+pub fn resize(window: &WebviewWindow, width: f64, height: f64) -> Result<(), PlatformError> {
+    use tauri::PhysicalSize;
+    window
+        .set_size(PhysicalSize::new(width, height))
+        .map_err(|e| PlatformError::Msg(format!("resize failed: {e}")))
+}
+
+pub fn move_window(window: &WebviewWindow, x: f64, y: f64) -> Result<(), PlatformError> {
+    use tauri::PhysicalPosition;
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| PlatformError::Msg(format!("move failed: {e}")))
+}
+
+/// Stack the window relative to others: "normal", "above", "below", or
+/// "overlay" (above, on every workspace, hidden from the taskbar).
+/// X11 maps these onto EWMH `_NET_WM_STATE_ABOVE`/`_BELOW`/`_STICKY` and
+/// macOS onto NSWindow levels. On Wayland a regular toplevel can only ask;
+/// guaranteed overlay behavior above panels needs the wlr layer-shell
+/// protocol, which WebKitGTK toplevels don't use, so compositors may ignore it.
+pub fn set_window_level(window: &WebviewWindow, level: &str) -> Result<(), PlatformError> {
+    let (top, bottom, overlay) = match level {
+        "normal" => (false, false, false),
+        "above" => (true, false, false),
+        "below" => (false, true, false),
+        "overlay" => (true, false, true),
+        other => return Err(PlatformError::Msg(format!("Unknown window level '{other}'"))),
+    };
+    let fail = |e: tauri::Error| PlatformError::Msg(format!("set_window_level failed: {e}"));
+    // Clear the opposite flag first so the WM never sees both set.
+    if top {
+        window.set_always_on_bottom(false).map_err(fail)?;
+        window.set_always_on_top(true).map_err(fail)?;
+    } else {
+        window.set_always_on_top(false).map_err(fail)?;
+        window.set_always_on_bottom(bottom).map_err(fail)?;
+    }
+    window.set_visible_on_all_workspaces(overlay).map_err(fail)?;
+    window.set_skip_taskbar(overlay).map_err(fail)?;
+    Ok(())
+}
+
+/// A rectangle in physical pixels.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Usable area (minus panels/docks) of the monitor the window is on.
+pub fn get_work_area(window: &WebviewWindow) -> Result<Rect, PlatformError> {
+    let monitor = window
+        .current_monitor()
+        .map_err(|e| PlatformError::Msg(format!("monitor lookup failed: {e}")))?
+        .ok_or_else(|| PlatformError::Msg("window is not on any monitor".into()))?;
+    let area = monitor.work_area();
+    Ok(Rect { x: area.position.x, y: area.position.y, width: area.size.width, height: area.size.height })
+}
+
+/// Move and resize the window to fill half ("left", "right", "top",
+/// "bottom"), a quarter ("top-left", …) or all ("maximize") of its monitor's
+/// work area. Returns the applied geometry.
+pub fn snap_window(window: &WebviewWindow, position: &str) -> Result<Rect, PlatformError> {
+    let area = get_work_area(window)?;
+    let (half_w, half_h) = (area.width / 2, area.height / 2);
+    let (right_x, bottom_y) = (area.x + half_w as i32, area.y + half_h as i32);
+    let target = match position {
+        "left" => Rect { width: half_w, ..area },
+        "right" => Rect { x: right_x, width: area.width - half_w, ..area },
+        "top" => Rect { height: half_h, ..area },
+        "bottom" => Rect { y: bottom_y, height: area.height - half_h, ..area },
+        "top-left" => Rect { width: half_w, height: half_h, ..area },
+        "top-right" => Rect { x: right_x, width: area.width - half_w, height: half_h, ..area },
+        "bottom-left" => Rect { y: bottom_y, width: half_w, height: area.height - half_h, ..area },
+        "bottom-right" => Rect { x: right_x, y: bottom_y, width: area.width - half_w, height: area.height - half_h },
+        "maximize" => area,
+        other => return Err(PlatformError::Msg(format!("Unknown snap position '{other}'"))),
+    };
+
+    let fail = |e: tauri::Error| PlatformError::Msg(format!("snap failed: {e}"));
+    // A maximized window ignores size changes on most window managers.
+    if window.is_maximized().map_err(fail)? {
+        window.unmaximize().map_err(fail)?;
+    }
+    window.set_size(tauri::PhysicalSize::new(target.width, target.height)).map_err(fail)?;
+    window.set_position(tauri::PhysicalPosition::new(target.x, target.y)).map_err(fail)?;
+    Ok(target)
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WindowState {
+    /// Physical outer position
+    pub x: i32,
+    pub y: i32,
+    /// Physical outer size
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub maximized: bool,
+    pub minimized: bool,
+    pub focused: bool,
+    pub visible: bool,
+    /// `None` when the monitor can't be determined (headless, mid-move)
+    pub monitor: Option<String>,
+    pub monitor_width: Option<u32>,
+    pub monitor_height: Option<u32>,
+}
+
+/// Snapshot of a window's geometry and state, e.g. to persist a layout
+/// and restore it later with `resize` / `move_window`.
+pub fn get_window_state(window: &WebviewWindow) -> Result<WindowState, PlatformError> {
+    let fail = |e: tauri::Error| PlatformError::Msg(format!("window state read failed: {e}"));
+    let pos = window.outer_position().map_err(fail)?;
+    let size = window.outer_size().map_err(fail)?;
+    let monitor = window.current_monitor().map_err(fail)?;
+    Ok(WindowState {
+        x: pos.x,
+        y: pos.y,
+        width: size.width,
+        height: size.height,
+        scale_factor: window.scale_factor().map_err(fail)?,
+        maximized: window.is_maximized().map_err(fail)?,
+        minimized: window.is_minimized().map_err(fail)?,
+        focused: window.is_focused().map_err(fail)?,
+        visible: window.is_visible().map_err(fail)?,
+        monitor: monitor.as_ref().and_then(|m| m.name().cloned()),
+        monitor_width: monitor.as_ref().map(|m| m.size().width),
+        monitor_height: monitor.as_ref().map(|m| m.size().height),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct StableGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    /// Reads never agreed within the timeout; values are the latest read.
+    pub unstable: bool,
+}
+
+/// Read outer position/size until two consecutive reads (one frame apart)
+/// agree. Some Wayland compositors report stale coordinates right after a
+/// move, so a single read can't be trusted for persistence. Must not run on
+/// the main thread, or the event loop can't deliver the updates.
+pub fn get_geometry_stable(
+    window: &WebviewWindow,
+    timeout: std::time::Duration,
+) -> Result<StableGeometry, PlatformError> {
+    let read = || -> Result<StableGeometry, PlatformError> {
+        let fail = |e: tauri::Error| PlatformError::Msg(format!("geometry read failed: {e}"));
+        let pos = window.outer_position().map_err(fail)?;
+        let size = window.outer_size().map_err(fail)?;
+        Ok(StableGeometry {
+            x: pos.x,
+            y: pos.y,
+            width: size.width,
+            height: size.height,
+            scale_factor: window.scale_factor().map_err(fail)?,
+            unstable: false,
+        })
+    };
+
+    let started = std::time::Instant::now();
+    let mut previous = read()?;
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(16));
+        let current = read()?;
+        if current == previous {
+            return Ok(current);
+        }
+        if started.elapsed() >= timeout {
+            return Ok(StableGeometry { unstable: true, ..current });
+        }
+        previous = current;
+    }
+}
+
+/// Reload the webview. With `ignore_cache`, WebKitGTK's
+/// `reload_bypass_cache()` refetches the document and its subresources
+/// without consulting the cache (Linux only).
+pub fn reload(window: &WebviewWindow, ignore_cache: bool) -> Result<(), PlatformError> {
+    if !ignore_cache {
+        return window
+            .eval("location.reload()")
+            .map_err(|e| PlatformError::Msg(format!("reload failed: {e}")));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        window
+            .with_webview(|webview| {
+                use webkit2gtk::WebViewExt;
+                webview.inner().reload_bypass_cache();
+            })
+            .map_err(|e| PlatformError::Msg(format!("reload failed: {e}")))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("cache-bypassing reload is only available on Linux".into()))
+    }
+}
+
+/// Run `f` with the window's GTK handle on the main thread and wait for its
+/// result. Callers must not be on the main thread themselves (use async
+/// commands), or this would deadlock.
+#[cfg(target_os = "linux")]
+fn with_gtk_window<T: Send + 'static>(
+    window: &WebviewWindow,
+    f: impl FnOnce(&gtk::ApplicationWindow) -> T + Send + 'static,
+) -> Result<T, PlatformError> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = window.clone();
+    window
+        .run_on_main_thread(move || {
+            let result = handle.gtk_window().map(|gtk_window| f(&gtk_window));
+            let _ = tx.send(result);
+        })
+        .map_err(|e| PlatformError::Msg(format!("main thread dispatch failed: {e}")))?;
+    rx.recv_timeout(std::time::Duration::from_secs(2))
+        .map_err(|_| PlatformError::Msg("main thread did not respond".into()))?
+        .map_err(|e| PlatformError::Msg(format!("no GTK window: {e}")))
+}
+
+/// Show the window on every workspace (EWMH `_NET_WM_STATE_STICKY` on X11).
+/// Wayland has no protocol for clients to request this, so it's refused
+/// there instead of silently doing nothing.
+pub fn set_sticky(window: &WebviewWindow, enabled: bool) -> Result<(), PlatformError> {
+    if is_wayland() {
+        return Err(PlatformError::Unsupported("sticky windows can't be requested on Wayland".into()));
+    }
+    window
+        .set_visible_on_all_workspaces(enabled)
+        .map_err(|e| PlatformError::Msg(format!("set_sticky failed: {e}")))
+}
+
+/// Read back the sticky state as reported by the window manager.
+pub fn is_sticky(window: &WebviewWindow) -> Result<bool, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        use gtk::prelude::*;
+
+        if is_wayland() {
+            return Err(PlatformError::Unsupported("sticky state isn't exposed on Wayland".into()));
+        }
+        with_gtk_window(window, |gtk_window| {
+            gtk_window
+                .window()
+                .map(|gdk_window| gdk_window.state().contains(gtk::gdk::WindowState::STICKY))
+                .unwrap_or(false)
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = window;
+        Err(PlatformError::Unsupported("sticky state can only be read on Linux".into()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct ShapeRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Clip the window to the union of `regions` (logical pixels relative to
+/// the window), or back to its full rectangle with `None`. On X11 this
+/// needs the SHAPE extension (present on every mainstream server) and clips
+/// both drawing and input. Wayland has no client-side shaping: only the
+/// input region is applied, so clicks outside pass through but the
+/// transparent area is still part of the surface. Must not be called from
+/// the main thread.
+pub fn set_window_shape(window: &WebviewWindow, regions: Option<Vec<ShapeRect>>) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        use gtk::prelude::*;
+
+        let wayland = is_wayland();
+        with_gtk_window(window, move |gtk_window| {
+            let region = regions.map(|rects| {
+                let rects: Vec<gtk::cairo::RectangleInt> = rects
+                    .iter()
+                    .map(|r| gtk::cairo::RectangleInt::new(r.x, r.y, r.width.max(0), r.height.max(0)))
+                    .collect();
+                gtk::cairo::Region::create_rectangles(&rects)
+            });
+            gtk_window.input_shape_combine_region(region.as_ref());
+            if !wayland {
+                gtk_window.shape_combine_region(region.as_ref());
+            }
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (window, regions);
+        Err(PlatformError::Unsupported("window shapes are only available on Linux".into()))
+    }
+}
+
+/// Set or clear the launcher badge. On Linux this broadcasts the Unity
+/// `LauncherEntry` signal, which is honored by Ubuntu Dock, Dash to Dock,
+/// Plank and KDE's task manager; GNOME's stock dash ignores it. Elsewhere
+/// Tauri's native badge API is used (macOS dock; Windows has no count badge).
+pub fn set_badge_count(
+    window: &WebviewWindow,
+    identifier: &str,
+    count: Option<i64>,
+) -> Result<(), PlatformError> {
+    let count = count.filter(|c| *c > 0);
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = window;
+        let app_uri = format!("application://{identifier}.desktop");
+        let props = match count {
+            Some(c) => format!("{{'count': <int64 {c}>, 'count-visible': <true>}}"),
+            None => "{'count': <int64 0>, 'count-visible': <false>}".to_string(),
+        };
+        let object_path = format!("/{}", identifier.replace(['.', '-'], "/"));
+        run_tool(
+            "gdbus",
+            &[
+                "emit",
+                "--session",
+                "--object-path",
+                &object_path,
+                "--signal",
+                "com.canonical.Unity.LauncherEntry.Update",
+                &app_uri,
+                &props,
+            ],
+        )
+        .map(|_| ())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = identifier;
+        window
+            .set_badge_count(count)
+            .map_err(|e| PlatformError::Msg(format!("set_badge_count failed: {e}")))
+    }
+}
+
+/// Process inspection
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FdTarget {
+    /// Raw link target, e.g. "/home/me/log.txt" or "socket:[12345]"
+    pub target: String,
+    /// "file" | "socket" | "pipe" | "anon" | "other"
+    pub kind: String,
+}
+
+/// Resolve `/proc/<pid>/fd/<fd>` to what it points at.
+pub fn resolve_fd(pid: u32, fd: i32) -> Result<FdTarget, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let link = format!("/proc/{pid}/fd/{fd}");
+        let target = std::fs::read_link(&link).map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => {
+                PlatformError::PermissionDenied(format!("cannot inspect fds of pid {pid}"))
+            }
+            std::io::ErrorKind::NotFound => {
+                PlatformError::Msg(format!("No fd {fd} for pid {pid}"))
+            }
+            _ => PlatformError::Io(e),
+        })?;
+        let target = target.display().to_string();
+        let kind = if target.starts_with("socket:") {
+            "socket"
+        } else if target.starts_with("pipe:") {
+            "pipe"
+        } else if target.starts_with("anon_inode:") {
+            "anon"
+        } else if target.starts_with('/') {
+            "file"
+        } else {
+            "other"
+        };
+        Ok(FdTarget { target, kind: kind.to_string() })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (pid, fd);
+        Err(PlatformError::Unsupported("fd resolution requires /proc".into()))
+    }
+}
+
+/// Scan `/proc/*/fd` for descriptors whose link target satisfies `matches`.
+/// Returns the matching PIDs and whether any process could not be inspected.
+#[cfg(target_os = "linux")]
+fn scan_proc_fds(matches: impl Fn(&std::path::Path) -> bool) -> (Vec<u32>, bool) {
+    let mut pids = Vec::new();
+    let mut partial = false;
+    let Ok(procs) = std::fs::read_dir("/proc") else { return (pids, true) };
+    for entry in procs.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            partial = true;
+            continue;
+        };
+        let holds = fds
+            .flatten()
+            .any(|fd| std::fs::read_link(fd.path()).map(|t| matches(&t)).unwrap_or(false));
+        if holds {
+            pids.push(pid);
+        }
+    }
+    (pids, partial)
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FileUsers {
+    pub pids: Vec<u32>,
+    /// Some processes' fds were unreadable (other users), so `pids` may be incomplete.
+    pub partial: bool,
+}
+
+/// PIDs that currently hold `path` open, found by scanning `/proc/*/fd`.
+pub fn is_file_in_use(path: &str) -> Result<FileUsers, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let target = std::fs::canonicalize(path)
+            .map_err(|_| PlatformError::Msg(format!("No such file: '{path}'")))?;
+        let (pids, partial) = scan_proc_fds(|link| link == target);
+        Ok(FileUsers { pids, partial })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        Err(PlatformError::Unsupported("open-file detection requires /proc".into()))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_name(pid: u32) -> String {
+    std::fs::read_to_string(format!("/proc/{pid}/comm"))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: String,
+}
+
+/// Find the process bound to a local TCP (listening) or UDP port by matching
+/// socket inodes from `/proc/net/*` against `/proc/*/fd`. Sockets owned by
+/// other users' processes are only visible with sufficient privilege.
+pub fn port_owner(port: u16, protocol: &str) -> Result<Option<PortOwner>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let (tables, state) = match protocol {
+            "tcp" => (["/proc/net/tcp", "/proc/net/tcp6"], Some("0A")), // LISTEN
+            "udp" => (["/proc/net/udp", "/proc/net/udp6"], None),
+            other => return Err(PlatformError::Msg(format!("Unknown protocol '{other}'"))),
+        };
+
+        let inode = tables.iter().find_map(|table| {
+            let raw = std::fs::read_to_string(table).ok()?;
+            raw.lines().skip(1).find_map(|line| {
+                let cols: Vec<&str> = line.split_whitespace().collect();
+                let local_port = cols.get(1)?.rsplit_once(':')?.1;
+                if u16::from_str_radix(local_port, 16).ok()? != port {
+                    return None;
+                }
+                if state.is_some_and(|s| cols.get(3) != Some(&s)) {
+                    return None;
+                }
+                cols.get(9).filter(|i| **i != "0").map(|i| i.to_string())
+            })
+        });
+        let Some(inode) = inode else { return Ok(None) };
+
+        let needle = format!("socket:[{inode}]");
+        let (pids, _) = scan_proc_fds(|target| target.as_os_str() == needle.as_str());
+        Ok(pids.first().map(|&pid| PortOwner { pid, name: process_name(pid) }))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (port, protocol);
+        Err(PlatformError::Unsupported("port owner lookup requires /proc".into()))
+    }
+}
+
+/// System identity
+
+pub fn get_hostname() -> Result<String, PlatformError> {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        // SAFETY: buf is writable for its full length.
+        let rc = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+        if rc != 0 {
+            return Err(PlatformError::Io(std::io::Error::last_os_error()));
+        }
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        Ok(String::from_utf8_lossy(&buf[..end]).into_owned())
+    }
+
+    #[cfg(not(unix))]
+    {
+        std::env::var("COMPUTERNAME")
+            .map_err(|_| PlatformError::Msg("Could not resolve hostname".into()))
+    }
+}
+
+/// The OS's own install-wide id: `/etc/machine-id` (or the D-Bus copy),
+/// `IOPlatformUUID` on macOS, `MachineGuid` on Windows.
+fn raw_machine_id() -> Result<String, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+            .iter()
+            .filter_map(|p| std::fs::read_to_string(p).ok())
+            .map(|id| id.trim().to_string())
+            .find(|id| !id.is_empty())
+            .ok_or_else(|| PlatformError::Unsupported("no /etc/machine-id on this system".into()))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let out = run_tool("ioreg", &["-rd1", "-c", "IOPlatformExpertDevice"])?;
+        out.lines()
+            .find(|l| l.contains("\"IOPlatformUUID\""))
+            .and_then(|l| l.rsplit('"').nth(1))
+            .map(str::to_string)
+            .ok_or_else(|| PlatformError::Unsupported("IOPlatformUUID not found".into()))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let out = run_tool(
+            "reg",
+            &["query", r"HKLM\SOFTWARE\Microsoft\Cryptography", "/v", "MachineGuid"],
+        )?;
+        out.lines()
+            .find(|l| l.contains("MachineGuid"))
+            .and_then(|l| l.split_whitespace().last())
+            .map(str::to_string)
+            .ok_or_else(|| PlatformError::Unsupported("MachineGuid not found".into()))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        Err(PlatformError::Unsupported("no machine id source on this platform".into()))
+    }
+}
+
+/// Stable per-device id for this app: SHA-256 over the app identifier and
+/// the OS machine id, hex-encoded. It's a one-way hash, so the real machine
+/// id can't be recovered from it, and different apps get unrelated ids.
+/// Errors when the OS has no id rather than inventing one.
+pub fn get_machine_id(app_identifier: &str) -> Result<String, PlatformError> {
+    use sha2::{Digest, Sha256};
+
+    let raw = raw_machine_id()?;
+    let digest = Sha256::new()
+        .chain_update(app_identifier.as_bytes())
+        .chain_update(b":")
+        .chain_update(raw.as_bytes())
+        .finalize();
+    Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// RFC 1123 host name: dot-separated labels of 1–63 alphanumerics or
+/// hyphens, not starting or ending with a hyphen, 253 chars max.
+#[cfg(feature = "privileged")]
+pub fn is_valid_hostname(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+/// Set the static (or, with `pretty`, the free-form pretty) hostname via
+/// `hostnamectl`. Needs root or a polkit grant.
+#[cfg(feature = "privileged")]
+pub fn set_hostname(hostname: &str, pretty: bool) -> Result<(), PlatformError> {
+    if pretty {
+        if hostname.trim().is_empty() || hostname.chars().any(char::is_control) {
+            return Err(PlatformError::Msg(format!("Invalid pretty hostname '{hostname}'")));
+        }
+    } else if !is_valid_hostname(hostname) {
+        return Err(PlatformError::Msg(format!("Invalid hostname '{hostname}' (RFC 1123)")));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut args = vec!["set-hostname", hostname];
+        if pretty {
+            args.push("--pretty");
+        }
+        run_tool("hostnamectl", &args).map(|_| ()).map_err(|e| match e {
+            PlatformError::Msg(msg)
+                if msg.contains("Access denied") || msg.contains("authentication") =>
+            {
+                PlatformError::PermissionDenied("changing the hostname requires root".into())
+            }
+            other => other,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("setting the hostname is only available on Linux".into()))
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FdInfo {
+    pub open: usize,
+    pub soft_limit: u64,
+    pub hard_limit: u64,
+}
+
+/// Open descriptor count of this process and its `RLIMIT_NOFILE`.
+pub fn get_fd_info() -> Result<FdInfo, PlatformError> {
+    #[cfg(unix)]
+    {
+        #[cfg(target_os = "linux")]
+        let fd_dir = "/proc/self/fd";
+        #[cfg(not(target_os = "linux"))]
+        let fd_dir = "/dev/fd";
+
+        // The directory handle used for listing shows up in the listing itself.
+        let open = std::fs::read_dir(fd_dir)?.count().saturating_sub(1);
+
+        let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        // SAFETY: limit is a valid, writable rlimit struct.
+        if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+            return Err(PlatformError::Io(std::io::Error::last_os_error()));
+        }
+        Ok(FdInfo {
+            open,
+            soft_limit: limit.rlim_cur as u64,
+            hard_limit: limit.rlim_max as u64,
+        })
+    }
+
+    #[cfg(not(unix))]
+    {
+        Err(PlatformError::Unsupported("file descriptor info is not available on Windows".into()))
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ResourceLimit {
+    pub resource: String,
+    /// `None` means unlimited
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+/// Map a resource name ("nofile", "nproc", "as", …) to its `RLIMIT_*`.
+#[cfg(unix)]
+fn rlimit_resource(name: &str) -> Result<RlimitResource, PlatformError> {
+    Ok(match name.to_ascii_lowercase().trim_start_matches("rlimit_") {
+        "nofile" => libc::RLIMIT_NOFILE,
+        "nproc" => libc::RLIMIT_NPROC,
+        "as" => libc::RLIMIT_AS,
+        "core" => libc::RLIMIT_CORE,
+        "cpu" => libc::RLIMIT_CPU,
+        "data" => libc::RLIMIT_DATA,
+        "fsize" => libc::RLIMIT_FSIZE,
+        "stack" => libc::RLIMIT_STACK,
+        "memlock" => libc::RLIMIT_MEMLOCK,
+        "rss" => libc::RLIMIT_RSS,
+        other => return Err(PlatformError::Msg(format!("Unknown resource '{other}'"))),
+    })
+}
+
+#[cfg(unix)]
+fn read_rlimit(resource: RlimitResource) -> Result<libc::rlimit, PlatformError> {
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    // SAFETY: limit is a valid, writable rlimit struct.
+    if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+        return Err(PlatformError::Io(std::io::Error::last_os_error()));
+    }
+    Ok(limit)
+}
+
+/// Current soft/hard limit of this process for `resource`.
+pub fn get_rlimit(resource: &str) -> Result<ResourceLimit, PlatformError> {
+    #[cfg(unix)]
+    {
+        let limit = read_rlimit(rlimit_resource(resource)?)?;
+        let finite = |v: libc::rlim_t| (v != libc::RLIM_INFINITY).then_some(v as u64);
+        Ok(ResourceLimit {
+            resource: resource.to_ascii_lowercase(),
+            soft: finite(limit.rlim_cur),
+            hard: finite(limit.rlim_max),
+        })
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = resource;
+        Err(PlatformError::Unsupported("resource limits are not available on Windows".into()))
+    }
+}
+
+/// Change this process's limit for `resource`; `None` keeps the current
+/// value and `u64::MAX` means unlimited. The soft limit can be raised up
+/// to the hard limit freely; raising the hard limit needs root.
+pub fn set_rlimit(resource: &str, soft: Option<u64>, hard: Option<u64>) -> Result<ResourceLimit, PlatformError> {
+    #[cfg(unix)]
+    {
+        let id = rlimit_resource(resource)?;
+        let mut limit = read_rlimit(id)?;
+        let value = |v: u64| if v == u64::MAX { libc::RLIM_INFINITY } else { v as libc::rlim_t };
+        if let Some(hard) = hard {
+            limit.rlim_max = value(hard);
+        }
+        if let Some(soft) = soft {
+            limit.rlim_cur = value(soft);
+        }
+        // SAFETY: limit is a valid rlimit struct.
+        if unsafe { libc::setrlimit(id, &limit) } != 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(match err.raw_os_error() {
+                Some(libc::EPERM) => PlatformError::PermissionDenied(
+                    "raising a hard limit requires root (CAP_SYS_RESOURCE)".into(),
+                ),
+                Some(libc::EINVAL) => PlatformError::Msg("the soft limit can't exceed the hard limit".into()),
+                _ => PlatformError::Io(err),
+            });
+        }
+        get_rlimit(resource)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (resource, soft, hard);
+        Err(PlatformError::Unsupported("resource limits are not available on Windows".into()))
+    }
+}
+
+/// The command-line arguments the app was started with. Snapshotted on
+/// first use, since `set_process_title` overwrites the argv memory that
+/// `std::env::args` reads from.
+pub fn launch_args() -> &'static [String] {
+    static ARGS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+    ARGS.get_or_init(|| std::env::args().collect())
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProcessTitle {
+    /// Name now in `comm` (`top`, `ps -o comm`), at most 15 bytes
+    pub comm: String,
+    /// Command line now in `/proc/self/cmdline` (`ps -f`, `top -c`), cut
+    /// to the length of the original argv; `None` if it couldn't be changed
+    pub cmdline: Option<String>,
+}
+
+/// Byte range of this process's argv strings (`arg_start`/`arg_end`,
+/// fields 48 and 49 of `/proc/self/stat`).
+#[cfg(target_os = "linux")]
+fn argv_region() -> Result<(usize, usize), PlatformError> {
+    let stat = std::fs::read_to_string("/proc/self/stat")?;
+    // Fields after the parenthesized comm start at field 3.
+    let fields: Vec<&str> = stat
+        .rsplit_once(')')
+        .map(|(_, rest)| rest.split_whitespace().collect())
+        .unwrap_or_default();
+    let field = |n: usize| fields.get(n - 3).and_then(|v| v.parse::<usize>().ok());
+    match (field(48), field(49)) {
+        (Some(start), Some(end)) if start > 0 && end > start => Ok((start, end)),
+        _ => Err(PlatformError::Unsupported("kernel doesn't report the argv region".into())),
+    }
+}
+
+/// Rename this process as shown by `ps`/`top`. `comm` is set with
+/// `prctl(PR_SET_NAME)`; Linux limits it to 15 bytes, so longer titles are
+/// truncated there (on a char boundary). The argv strings are then
+/// overwritten in place so `ps -f` and `/proc/<pid>/cmdline` show the title
+/// too. That space is only as long as the original command line, so the
+/// title is cut to fit; `launch_args` keeps the original arguments.
+pub fn set_process_title(title: &str) -> Result<ProcessTitle, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let truncate = |max: usize| {
+            let mut end = title.len().min(max);
+            while !title.is_char_boundary(end) {
+                end -= 1;
+            }
+            &title[..end]
+        };
+        let short = truncate(15);
+        let cname = std::ffi::CString::new(short)
+            .map_err(|_| PlatformError::Msg("Title must not contain NUL bytes".into()))?;
+        // SAFETY: PR_SET_NAME reads a NUL-terminated string of at most 16 bytes.
+        if unsafe { libc::prctl(libc::PR_SET_NAME, cname.as_ptr() as libc::c_ulong, 0, 0, 0) } == -1 {
+            return Err(PlatformError::Io(std::io::Error::last_os_error()));
+        }
+        // prctl only renames the calling thread; also rename the main thread,
+        // which is what `ps` shows for the process.
+        std::fs::write(format!("/proc/self/task/{}/comm", std::process::id()), short)?;
+
+        // Keep the real arguments readable before their memory is reused.
+        launch_args();
+        let cmdline = argv_region().ok().map(|(start, end)| {
+            // Leave room for the terminating NUL.
+            let applied = truncate(end - start - 1);
+            // SAFETY: [arg_start, arg_end) is this process's own argv block,
+            // mapped writable on the initial stack for the process lifetime.
+            // It is only read through `std::env::args`, which `launch_args`
+            // has already snapshotted; NUL-filling keeps every argv pointer
+            // pointing at a terminated (now empty) string.
+            unsafe {
+                let region = std::slice::from_raw_parts_mut(start as *mut u8, end - start);
+                region.fill(0);
+                region[..applied.len()].copy_from_slice(applied.as_bytes());
+            }
+            applied.to_string()
+        });
+        Ok(ProcessTitle { comm: short.to_string(), cmdline })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = title;
+        Err(PlatformError::Unsupported("process titles can only be changed on Linux".into()))
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProcessNetwork {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    /// The process is in the same network namespace as this app, so the
+    /// counters cover every process in it, not just `pid`
+    pub shared_namespace: bool,
+}
+
+/// Cumulative traffic seen by the network namespace of `pid`, read from
+/// `/proc/<pid>/net/dev` (loopback excluded). This is only per-process
+/// when the process has its own namespace (containers, sandboxes); Linux
+/// has no per-process byte counters without eBPF or netfilter accounting.
+/// `None` when the process is gone or its stats aren't readable.
+pub fn get_process_network(pid: u32) -> Result<Option<ProcessNetwork>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let Ok(raw) = std::fs::read_to_string(format!("/proc/{pid}/net/dev")) else {
+            return Ok(None);
+        };
+        let (mut rx_bytes, mut tx_bytes) = (0u64, 0u64);
+        // "  eth0: rx_bytes rx_packets ... (8 rx fields) tx_bytes ..."
+        for line in raw.lines().skip(2) {
+            let Some((iface, counters)) = line.split_once(':') else { continue };
+            if iface.trim() == "lo" {
+                continue;
+            }
+            let fields: Vec<u64> = counters.split_whitespace().filter_map(|f| f.parse().ok()).collect();
+            if fields.len() >= 9 {
+                rx_bytes += fields[0];
+                tx_bytes += fields[8];
+            }
+        }
+        let ns = |p: &str| std::fs::read_link(format!("/proc/{p}/ns/net")).ok();
+        let target_ns = ns(&pid.to_string());
+        let shared_namespace = target_ns.is_none() || target_ns == ns("self");
+        Ok(Some(ProcessNetwork { rx_bytes, tx_bytes, shared_namespace }))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Err(PlatformError::Unsupported("process network stats are only available on Linux".into()))
+    }
+}
+
+/// Hardware
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CpuScaling {
+    pub cpu: usize,
+    pub governor: String,
+    pub cur_khz: u64,
+    pub min_khz: u64,
+    pub max_khz: u64,
+}
+
+/// Write a sysfs attribute, turning EACCES/EPERM into `PermissionDenied`.
+#[cfg(target_os = "linux")]
+fn sysfs_write(path: &std::path::Path, value: &str) -> Result<(), PlatformError> {
+    std::fs::write(path, value).map_err(|e| match e.kind() {
+        std::io::ErrorKind::PermissionDenied => PlatformError::PermissionDenied(format!(
+            "writing {} requires root or a udev rule",
+            path.display()
+        )),
+        _ => PlatformError::Io(e),
+    })
+}
+
+/// The `cpufreq` directories of all CPUs, sorted by CPU index.
+#[cfg(target_os = "linux")]
+fn cpufreq_dirs() -> Vec<(usize, PathBuf)> {
+    let mut dirs: Vec<(usize, PathBuf)> = std::fs::read_dir("/sys/devices/system/cpu")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let index = name.strip_prefix("cpu")?.parse::<usize>().ok()?;
+            let freq = entry.path().join("cpufreq");
+            freq.is_dir().then_some((index, freq))
+        })
+        .collect();
+    dirs.sort_by_key(|(index, _)| *index);
+    dirs
+}
+
+/// Per-core governor and frequencies from cpufreq.
+pub fn get_cpu_scaling() -> Result<Vec<CpuScaling>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let read = |dir: &PathBuf, file: &str| {
+            std::fs::read_to_string(dir.join(file)).map(|s| s.trim().to_string()).unwrap_or_default()
+        };
+        let cores: Vec<CpuScaling> = cpufreq_dirs()
+            .into_iter()
+            .map(|(cpu, dir)| CpuScaling {
+                cpu,
+                governor: read(&dir, "scaling_governor"),
+                cur_khz: read(&dir, "scaling_cur_freq").parse().unwrap_or(0),
+                min_khz: read(&dir, "scaling_min_freq").parse().unwrap_or(0),
+                max_khz: read(&dir, "scaling_max_freq").parse().unwrap_or(0),
+            })
+            .collect();
+        if cores.is_empty() {
+            return Err(PlatformError::Unsupported("cpufreq is not exposed on this system".into()));
+        }
+        Ok(cores)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("CPU scaling info is only available on Linux".into()))
+    }
+}
+
+/// Set the scaling governor on every core (needs root).
+pub fn set_cpu_governor(governor: &str) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let dirs = cpufreq_dirs();
+        if dirs.is_empty() {
+            return Err(PlatformError::Unsupported("cpufreq is not exposed on this system".into()));
+        }
+        for (cpu, dir) in dirs {
+            let available = std::fs::read_to_string(dir.join("scaling_available_governors"))
+                .unwrap_or_default();
+            if !available.split_whitespace().any(|g| g == governor) {
+                return Err(PlatformError::Msg(format!(
+                    "Governor '{governor}' is not available on cpu{cpu} (available: {})",
+                    available.trim()
+                )));
+            }
+            sysfs_write(&dir.join("scaling_governor"), governor)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = governor;
+        Err(PlatformError::Unsupported("CPU governors are only available on Linux".into()))
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MemoryStatus {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    pub available_percent: f64,
+    /// PSI `some avg10`: % of the last 10s some task stalled on memory
+    pub psi_some_avg10: Option<f64>,
+    /// PSI `full avg10`: % of the last 10s all tasks stalled on memory
+    pub psi_full_avg10: Option<f64>,
+}
+
+/// Available memory from `/proc/meminfo`, plus pressure stall info from
+/// `/proc/pressure/memory` when the kernel has PSI enabled.
+pub fn memory_status() -> Result<MemoryStatus, PlatformError> {
+    let raw = std::fs::read_to_string("/proc/meminfo")?;
+    let field = |key: &str| {
+        raw.lines()
+            .find_map(|l| l.strip_prefix(key))
+            .and_then(|v| v.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+    };
+    let total_bytes = field("MemTotal:").ok_or_else(|| PlatformError::Msg("meminfo has no MemTotal".into()))?;
+    let available_bytes = field("MemAvailable:").or_else(|| field("MemFree:")).unwrap_or(0);
+
+    // "some avg10=0.00 avg60=0.00 avg300=0.00 total=0"
+    let psi = std::fs::read_to_string("/proc/pressure/memory").unwrap_or_default();
+    let avg10 = |kind: &str| {
+        psi.lines()
+            .find(|l| l.starts_with(kind))
+            .and_then(|l| l.split_whitespace().find_map(|f| f.strip_prefix("avg10=")))
+            .and_then(|v| v.parse().ok())
+    };
+
+    Ok(MemoryStatus {
+        total_bytes,
+        available_bytes,
+        available_percent: available_bytes as f64 * 100.0 / total_bytes.max(1) as f64,
+        psi_some_avg10: avg10("some"),
+        psi_full_avg10: avg10("full"),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BatteryStatus {
+    pub percent: f64,
+    /// "charging" | "discharging" | "full" | "not-charging" | "unknown"
+    pub state: String,
+}
+
+/// Combined level of all batteries from `/sys/class/power_supply`.
+/// Returns `None` on machines without a battery.
+pub fn battery_status() -> Result<Option<BatteryStatus>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut now = 0.0;
+        let mut full = 0.0;
+        let mut state = None;
+        for entry in std::fs::read_dir("/sys/class/power_supply")?.flatten() {
+            let dir = entry.path();
+            let read = |f: &str| std::fs::read_to_string(dir.join(f)).map(|s| s.trim().to_string());
+            if read("type").ok().as_deref() != Some("Battery") {
+                continue;
+            }
+            // Prefer energy/charge counters so multiple batteries weigh correctly.
+            let counters = read("energy_now").and_then(|n| Ok((n, read("energy_full")?)))
+                .or_else(|_| read("charge_now").and_then(|n| Ok((n, read("charge_full")?))));
+            match counters {
+                Ok((n, f)) => {
+                    now += n.parse::<f64>().unwrap_or(0.0);
+                    full += f.parse::<f64>().unwrap_or(0.0);
+                }
+                Err(_) => {
+                    now += read("capacity").ok().and_then(|c| c.parse().ok()).unwrap_or(0.0);
+                    full += 100.0;
+                }
+            }
+            let status = read("status").unwrap_or_default().to_lowercase().replace(' ', "-");
+            // A charging battery wins over an idle one when there are several.
+            if state.is_none() || status == "charging" {
+                state = Some(status);
+            }
+        }
+        let Some(state) = state else { return Ok(None) };
+        let percent = if full > 0.0 { (now / full * 100.0).clamp(0.0, 100.0) } else { 0.0 };
+        let state = if state.is_empty() { "unknown".to_string() } else { state };
+        Ok(Some(BatteryStatus { percent, state }))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("battery status is only available on Linux".into()))
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PowerProfiles {
+    pub active: String,
+    pub available: Vec<String>,
+}
+
+#[cfg(target_os = "linux")]
+const POWER_PROFILES_DEST: &str = "org.freedesktop.UPower.PowerProfiles";
+#[cfg(target_os = "linux")]
+const POWER_PROFILES_PATH: &str = "/org/freedesktop/UPower/PowerProfiles";
+
+/// Active and available profiles from power-profiles-daemon.
+pub fn get_power_profile() -> Result<PowerProfiles, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let unavailable = |_| PlatformError::Unsupported("power-profiles-daemon is not running".into());
+        let active = dbus_get_property(
+            "--system", POWER_PROFILES_DEST, POWER_PROFILES_PATH, POWER_PROFILES_DEST, "ActiveProfile",
+        )
+        .map_err(unavailable)?;
+        let profiles = dbus_get_property(
+            "--system", POWER_PROFILES_DEST, POWER_PROFILES_PATH, POWER_PROFILES_DEST, "Profiles",
+        )
+        .map_err(unavailable)?;
+
+        // Profiles is `[{'Profile': <'power-saver'>, 'Driver': <'…'>}, …]`.
+        let strings = gvariant_strings(&profiles);
+        let available = strings
+            .windows(2)
+            .filter(|w| w[0] == "Profile")
+            .map(|w| w[1].clone())
+            .collect();
+        Ok(PowerProfiles {
+            active: gvariant_strings(&active).into_iter().next().unwrap_or_default(),
+            available,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("power profiles are only available on Linux".into()))
+    }
+}
+
+/// Call `on_change(active)` from a background thread whenever the active
+/// profile changes, whoever changed it (this app, the shell's power menu,
+/// `powerprofilesctl`), until `stop` is set. Driven by the daemon's
+/// `PropertiesChanged` signal through `gdbus monitor`.
+pub fn watch_power_profile(
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    mut on_change: impl FnMut(String) + Send + 'static,
+) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut last = get_power_profile()?.active;
+        let args = ["monitor", "--system", "--dest", POWER_PROFILES_DEST, "--object-path", POWER_PROFILES_PATH];
+        watch_tool_lines("gdbus", args.map(String::from).to_vec(), stop, move |line| {
+            if !line.contains("PropertiesChanged") || !line.contains("ActiveProfile") {
+                return;
+            }
+            if let Ok(current) = get_power_profile() {
+                if current.active != last {
+                    last = current.active.clone();
+                    on_change(current.active);
+                }
+            }
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (stop, &mut on_change);
+        Err(PlatformError::Unsupported("power profiles are only available on Linux".into()))
+    }
+}
+
+pub fn set_power_profile(profile: &str) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let current = get_power_profile()?;
+        if !current.available.iter().any(|p| p == profile) {
+            return Err(PlatformError::Msg(format!(
+                "Unknown power profile '{profile}' (available: {})",
+                current.available.join(", ")
+            )));
+        }
+        let value = format!("<'{profile}'>");
+        run_tool(
+            "gdbus",
+            &[
+                "call", "--system", "--dest", POWER_PROFILES_DEST, "--object-path", POWER_PROFILES_PATH,
+                "--method", "org.freedesktop.DBus.Properties.Set", POWER_PROFILES_DEST, "ActiveProfile", &value,
+            ],
+        )
+        .map(|_| ())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = profile;
+        Err(PlatformError::Unsupported("power profiles are only available on Linux".into()))
+    }
+}
+
+/// Milliseconds since the last user input. Mutter's IdleMonitor on GNOME
+/// (X11 and Wayland), else `xprintidle` on X11.
+pub fn get_idle_time_ms() -> Result<u64, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("gnome") {
+            // "(uint64 12345,)"
+            let reply = run_tool(
+                "gdbus",
+                &[
+                    "call", "--session",
+                    "--dest", "org.gnome.Mutter.IdleMonitor",
+                    "--object-path", "/org/gnome/Mutter/IdleMonitor/Core",
+                    "--method", "org.gnome.Mutter.IdleMonitor.GetIdletime",
+                ],
+            )?;
+            return reply
+                .split(|c: char| !c.is_ascii_digit())
+                .filter(|n| !n.is_empty())
+                .nth(1)
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| PlatformError::Msg(format!("Unexpected idle time reply '{reply}'")));
+        }
+        if is_wayland() {
+            return Err(PlatformError::Unsupported("no idle-time backend for this Wayland session".into()));
+        }
+        let out = run_tool("xprintidle", &[])?;
+        out.parse().map_err(|_| PlatformError::Msg(format!("Unexpected xprintidle output '{out}'")))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("idle time is only available on Linux".into()))
+    }
+}
+
+/// Screen-blank timeout in seconds (0 = never). GNOME `idle-delay`, KDE
+/// PowerDevil's DPMS setting, or the X11 screensaver via `xset`.
+pub fn get_idle_timeout() -> Result<u32, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("gnome") {
+            let value = run_tool("gsettings", &["get", "org.gnome.desktop.session", "idle-delay"])?;
+            // "uint32 300"
+            return value
+                .rsplit(' ')
+                .next()
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| PlatformError::Msg(format!("Unexpected idle-delay '{value}'")));
+        }
+        if desktop_is("kde") {
+            let value = run_tool(
+                kde_config_tool(false),
+                &["--file", "powermanagementprofilesrc", "--group", "AC", "--group", "DPMSControl", "--key", "idleTime"],
+            )?;
+            // No DPMSControl group means PowerDevil never blanks the screen.
+            if value.is_empty() {
+                return Ok(0);
+            }
+            return value
+                .parse()
+                .map_err(|_| PlatformError::Msg(format!("Unexpected DPMS idleTime '{value}'")));
+        }
+        if !is_wayland() {
+            let query = run_tool("xset", &["q"])?;
+            // "  timeout:  600    cycle:  600"
+            return query
+                .lines()
+                .find_map(|l| l.trim().strip_prefix("timeout:"))
+                .and_then(|rest| rest.split_whitespace().next())
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| PlatformError::Msg("xset did not report a timeout".into()));
+        }
+        Err(PlatformError::Unsupported("no idle-timeout backend for this session".into()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("idle timeout is only available on Linux".into()))
+    }
+}
+
+/// 0 disables blanking. On KDE, PowerDevil is asked to reload so the new
+/// value applies without logging out.
+pub fn set_idle_timeout(seconds: u32) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let value = seconds.to_string();
+        if desktop_is("gnome") {
+            let typed = format!("uint32 {seconds}");
+            return run_tool("gsettings", &["set", "org.gnome.desktop.session", "idle-delay", &typed])
+                .map(|_| ());
+        }
+        if desktop_is("kde") {
+            // Deleting the only key drops the DPMSControl group, which is how
+            // PowerDevil represents "never blank".
+            let write = if seconds == 0 { "--delete" } else { value.as_str() };
+            run_tool(
+                kde_config_tool(true),
+                &["--file", "powermanagementprofilesrc", "--group", "AC", "--group", "DPMSControl", "--key", "idleTime", write],
+            )?;
+            run_tool(
+                "gdbus",
+                &[
+                    "call", "--session",
+                    "--dest", "org.kde.Solid.PowerManagement",
+                    "--object-path", "/org/kde/Solid/PowerManagement",
+                    "--method", "org.kde.Solid.PowerManagement.refreshStatus",
+                ],
+            )?;
+            return Ok(());
+        }
+        if !is_wayland() {
+            let timeout = if seconds == 0 { "off" } else { value.as_str() };
+            return run_tool("xset", &["s", timeout]).map(|_| ());
+        }
+        Err(PlatformError::Unsupported("no idle-timeout backend for this session".into()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = seconds;
+        Err(PlatformError::Unsupported("idle timeout is only available on Linux".into()))
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct VideoAccel {
+    pub vaapi: bool,
+    pub vdpau: bool,
+    pub nvenc: bool,
+    /// DRM render nodes and NVIDIA GPU names
+    pub devices: Vec<String>,
+}
+
+/// Probe hardware video acceleration. VA-API is confirmed with `vainfo`
+/// when it's installed (else a render node is taken as enough), VDPAU with
+/// `vdpauinfo`, NVENC with `nvidia-smi`. Nothing found is all false, not
+/// an error.
+pub fn get_video_accel() -> VideoAccel {
+    #[cfg(target_os = "linux")]
+    {
+        let mut devices: Vec<String> = std::fs::read_dir("/dev/dri")
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| e.file_name().to_string_lossy().starts_with("renderD"))
+                    .map(|e| e.path().display().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        devices.sort();
+
+        let vaapi = !devices.is_empty()
+            && match run_tool("vainfo", &["--display", "drm"]) {
+                Ok(out) => out.contains("VAProfile"),
+                Err(PlatformError::Unsupported(_)) => true,
+                Err(_) => false,
+            };
+        let vdpau = run_tool("vdpauinfo", &[]).is_ok();
+        let nvidia = run_tool("nvidia-smi", &["--query-gpu=name", "--format=csv,noheader"])
+            .map(|out| out.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let nvenc = !nvidia.is_empty();
+        devices.extend(nvidia);
+
+        VideoAccel { vaapi, vdpau, nvenc, devices }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        VideoAccel::default()
+    }
+}
+
+/// Displays
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DisplayScale {
+    /// Connector name, e.g. "eDP-1" or "DP-2"
+    pub connector: String,
+    pub scale: f64,
+}
+
+/// A logical monitor from Mutter's `DisplayConfig.GetCurrentState`.
+#[cfg(target_os = "linux")]
+struct MutterLogicalMonitor {
+    x: i32,
+    y: i32,
+    scale: f64,
+    transform: u32,
+    primary: bool,
+    connectors: Vec<String>,
+}
+
+#[cfg(target_os = "linux")]
+struct MutterState {
+    serial: u32,
+    logical: Vec<MutterLogicalMonitor>,
+    /// connector -> (current mode id, scales that mode supports)
+    modes: std::collections::HashMap<String, (String, Vec<f64>)>,
+}
+
+/// Read Mutter's display layout over D-Bus. The reply is GVariant text, of
+/// which only the parts needed to re-apply the layout are picked out.
+#[cfg(target_os = "linux")]
+fn mutter_display_state() -> Result<MutterState, PlatformError> {
+    let reply = run_tool(
+        "gdbus",
+        &[
+            "call", "--session",
+            "--dest", "org.gnome.Mutter.DisplayConfig",
+            "--object-path", "/org/gnome/Mutter/DisplayConfig",
+            "--method", "org.gnome.Mutter.DisplayConfig.GetCurrentState",
+        ],
+    )
+    .map_err(|e| match e {
+        PlatformError::Msg(msg) if msg.contains("ServiceUnknown") || msg.contains("NameHasNoOwner") => {
+            PlatformError::Unsupported("org.gnome.Mutter.DisplayConfig is not available".into())
+        }
+        other => other,
+    })?;
+    let unexpected = || PlatformError::Msg("Unexpected DisplayConfig reply".into());
+
+    // "(uint32 7, [monitors…], [logical monitors…], {…})"
+    let serial = reply
+        .strip_prefix("(uint32 ")
+        .and_then(|r| r.split(',').next())
+        .and_then(|n| n.trim().parse().ok())
+        .ok_or_else(unexpected)?;
+
+    // Monitors: "(('eDP-1', vendor, product, serial), [modes], {props})";
+    // modes: "('2256x1504@59.999', 2256, 1504, 59.99, 1.5, [1.0, 1.25, …], {'is-current': <true>, …})"
+    let monitor_re = regex::Regex::new(r"\(\('([^']+)', '[^']*', '[^']*', '[^']*'\), \[").expect("valid regex");
+    let mode_re = regex::Regex::new(r"\('([^']+)', \d+, \d+, [0-9.]+, [0-9.]+, \[([^\]]*)\], \{([^}]*)\}\)")
+        .expect("valid regex");
+    let starts: Vec<(usize, String)> =
+        monitor_re.captures_iter(&reply).map(|c| (c.get(0).map_or(0, |m| m.start()), c[1].to_string())).collect();
+    let mut modes = std::collections::HashMap::new();
+    for (n, (start, connector)) in starts.iter().enumerate() {
+        let end = starts.get(n + 1).map_or(reply.len(), |(next, _)| *next);
+        let current = mode_re.captures_iter(&reply[*start..end]).find(|c| c[3].contains("'is-current': <true>"));
+        if let Some(mode) = current {
+            let scales = mode[2].split(',').filter_map(|v| v.trim().parse().ok()).collect();
+            modes.insert(connector.clone(), (mode[1].to_string(), scales));
+        }
+    }
+
+    // Logical monitors: "(0, 0, 1.25, uint32 0, true, [('eDP-1', …)], {…})"
+    let logical_re = regex::Regex::new(
+        r"\((-?\d+), (-?\d+), ([0-9.]+), (?:uint32 )?(\d+), (true|false), \[([^\]]*)\]",
+    )
+    .expect("valid regex");
+    let logical = logical_re
+        .captures_iter(&reply)
+        .filter_map(|c| {
+            Some(MutterLogicalMonitor {
+                x: c[1].parse().ok()?,
+                y: c[2].parse().ok()?,
+                scale: c[3].parse().ok()?,
+                transform: c[4].parse().ok()?,
+                primary: &c[5] == "true",
+                // Each monitor is a 4-string tuple; the connector comes first.
+                connectors: gvariant_strings(&c[6]).into_iter().step_by(4).collect(),
+            })
+        })
+        .collect();
+    Ok(MutterState { serial, logical, modes })
+}
+
+/// Enabled outputs and their scale from `kscreen-doctor --outputs`.
+#[cfg(target_os = "linux")]
+fn kscreen_scales() -> Result<Vec<DisplayScale>, PlatformError> {
+    // "Output: 1 eDP-1 enabled connected priority 1 …" then "\tScale: 1.25"
+    let outputs = run_tool("kscreen-doctor", &["--outputs"])?;
+    let mut scales = Vec::new();
+    let mut current: Option<String> = None;
+    for line in outputs.lines().map(strip_ansi) {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Output:") {
+            current = rest
+                .contains(" enabled")
+                .then(|| rest.split_whitespace().nth(1).map(str::to_string))
+                .flatten();
+        } else if let (Some(connector), Some(scale)) = (&current, line.strip_prefix("Scale:")) {
+            if let Ok(scale) = scale.trim().parse() {
+                scales.push(DisplayScale { connector: connector.clone(), scale });
+            }
+        }
+    }
+    Ok(scales)
+}
+
+/// Scale of every active output, by connector name. GNOME is read through
+/// Mutter's `DisplayConfig` D-Bus interface and KDE through
+/// `kscreen-doctor`; other desktops are unsupported.
+pub fn get_display_scales() -> Result<Vec<DisplayScale>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("kde") {
+            return kscreen_scales();
+        }
+        if desktop_is("gnome") {
+            let state = mutter_display_state()?;
+            return Ok(state
+                .logical
+                .iter()
+                .flat_map(|lm| lm.connectors.iter().map(|c| DisplayScale { connector: c.clone(), scale: lm.scale }))
+                .collect());
+        }
+        Err(PlatformError::Unsupported("no display configuration backend for this desktop".into()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("display scaling is only available on Linux".into()))
+    }
+}
+
+/// Apply a (fractional) scale to the output on `connector`. KDE goes
+/// through `kscreen-doctor`; GNOME re-applies its current layout through
+/// `DisplayConfig.ApplyMonitorsConfig` with the new scale, which must be one
+/// Mutter lists as supported for the output's mode (fractional values need
+/// its experimental `scale-monitor-framebuffer` feature). Positions are kept
+/// as they are, so Mutter may reject a multi-monitor layout that would then
+/// overlap.
+pub fn set_display_scale(connector: &str, scale: f64) -> Result<(), PlatformError> {
+    if !(0.5..=4.0).contains(&scale) {
+        return Err(PlatformError::Msg(format!("Scale {scale} is out of range (0.5–4.0)")));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let unknown = || PlatformError::Msg(format!("No active monitor on connector '{connector}'"));
+        if desktop_is("kde") {
+            if !kscreen_scales()?.iter().any(|s| s.connector == connector) {
+                return Err(unknown());
+            }
+            return run_tool("kscreen-doctor", &[&format!("output.{connector}.scale.{scale}")]).map(|_| ());
+        }
+        if desktop_is("gnome") {
+            let state = mutter_display_state()?;
+            let (_, supported) = state.modes.get(connector).ok_or_else(unknown)?;
+            let scale = supported
+                .iter()
+                .copied()
+                .find(|s| (s - scale).abs() < 0.01)
+                .ok_or_else(|| {
+                    PlatformError::Msg(format!("Scale {scale} is not supported on '{connector}' (supported: {supported:?})"))
+                })?;
+            if !state.logical.iter().any(|lm| lm.connectors.iter().any(|c| c == connector)) {
+                return Err(unknown());
+            }
+
+            let mut logical = Vec::new();
+            for lm in &state.logical {
+                let lm_scale = if lm.connectors.iter().any(|c| c == connector) { scale } else { lm.scale };
+                let mut monitors = Vec::new();
+                for c in &lm.connectors {
+                    let (mode, _) = state.modes.get(c).ok_or_else(|| PlatformError::Msg(format!("No current mode for '{c}'")))?;
+                    monitors.push(format!("('{c}', '{mode}', @a{{sv}} {{}})"));
+                }
+                logical.push(format!(
+                    "({}, {}, {lm_scale:?}, uint32 {}, {}, [{}])",
+                    lm.x,
+                    lm.y,
+                    lm.transform,
+                    lm.primary,
+                    monitors.join(", ")
+                ));
+            }
+            let serial = state.serial.to_string();
+            let logical = format!("[{}]", logical.join(", "));
+            // Method 2 = persistent (written to monitors.xml).
+            return run_tool(
+                "gdbus",
+                &[
+                    "call", "--session",
+                    "--dest", "org.gnome.Mutter.DisplayConfig",
+                    "--object-path", "/org/gnome/Mutter/DisplayConfig",
+                    "--method", "org.gnome.Mutter.DisplayConfig.ApplyMonitorsConfig",
+                    &serial, "2", &logical, "@a{sv} {}",
+                ],
+            )
+            .map(|_| ());
+        }
+        Err(PlatformError::Unsupported("no display configuration backend for this desktop".into()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = connector;
+        Err(PlatformError::Unsupported("display scaling is only available on Linux".into()))
+    }
+}
+
+/// Drop ANSI color escapes (`ESC[…m`) from tool output.
+#[cfg(target_os = "linux")]
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Icons
+
+/// Active icon theme name, falling back to `hicolor`. KDE keeps it in
+/// `kdeglobals`; it's checked first because KDE systems usually also have
+/// gsettings (for GTK apps) reporting GNOME's theme.
+pub fn get_icon_theme() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("kde") {
+            let read = run_tool(kde_config_tool(false), &["--file", "kdeglobals", "--group", "Icons", "--key", "Theme"]);
+            return match read {
+                Ok(theme) if !theme.is_empty() => theme,
+                // Plasma's default when nothing is configured.
+                _ => "breeze".to_string(),
+            };
+        }
+        if let Ok(theme) = run_tool("gsettings", &["get", "org.gnome.desktop.interface", "icon-theme"]) {
+            let theme = theme.trim_matches('\'');
+            if !theme.is_empty() {
+                return theme.to_string();
+            }
+        }
+    }
+    "hicolor".to_string()
+}
+
+/// Base directories searched for icon themes, in XDG priority order.
+#[cfg(target_os = "linux")]
+fn icon_base_dirs() -> Vec<PathBuf> {
+    let mut bases = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        bases.push(home.join(".icons"));
+    }
+    if let Some(data) = dirs::data_dir() {
+        bases.push(data.join("icons"));
+    }
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    bases.extend(data_dirs.split(':').filter(|d| !d.is_empty()).map(|d| PathBuf::from(d).join("icons")));
+    bases
+}
+
+/// The theme followed by its `Inherits=` chain, always ending in `hicolor`.
+#[cfg(target_os = "linux")]
+fn icon_theme_chain(theme: &str, bases: &[PathBuf]) -> Vec<String> {
+    let mut chain: Vec<String> = Vec::new();
+    let mut queue = vec![theme.to_string()];
+    while let Some(name) = queue.pop() {
+        if chain.contains(&name) {
+            continue;
+        }
+        let inherits = bases
+            .iter()
+            .find_map(|b| std::fs::read_to_string(b.join(&name).join("index.theme")).ok())
+            .and_then(|index| {
+                index.lines().find_map(|l| l.strip_prefix("Inherits=").map(str::to_string))
+            });
+        chain.push(name);
+        if let Some(parents) = inherits {
+            queue.extend(parents.split(',').rev().map(|p| p.trim().to_string()));
+        }
+    }
+    if !chain.iter().any(|t| t == "hicolor") {
+        chain.push("hicolor".to_string());
+    }
+    chain
+}
+
+/// Resolve an icon name to a file in the current theme (then its parents,
+/// then `hicolor`, then `/usr/share/pixmaps`). Sizes closest to `size` win;
+/// scalable icons rank just after an exact match. Absolute paths pass through.
+pub fn resolve_icon(name: &str, size: u32) -> Option<String> {
+    let as_path = std::path::Path::new(name);
+    if as_path.is_absolute() {
+        return as_path.is_file().then(|| name.to_string());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let bases = icon_base_dirs();
+        let extensions = ["png", "svg", "xpm"];
+        for theme in icon_theme_chain(&get_icon_theme(), &bases) {
+            for base in &bases {
+                let theme_dir = base.join(&theme);
+                let Ok(size_dirs) = std::fs::read_dir(&theme_dir) else { continue };
+                let mut ranked: Vec<(u32, PathBuf)> = size_dirs
+                    .flatten()
+                    .filter(|e| e.path().is_dir())
+                    .map(|e| {
+                        let dir_name = e.file_name().to_string_lossy().into_owned();
+                        let dir_size = dir_name
+                            .split(['x', '@'])
+                            .next()
+                            .and_then(|n| n.parse::<u32>().ok());
+                        let rank = match dir_size {
+                            Some(s) if s == size => 0,
+                            _ if dir_name == "scalable" => 1,
+                            Some(s) => 2 + s.abs_diff(size),
+                            None => u32::MAX,
+                        };
+                        (rank, e.path())
+                    })
+                    .collect();
+                ranked.sort_by_key(|(rank, _)| *rank);
+                for (_, size_dir) in ranked {
+                    let Ok(categories) = std::fs::read_dir(&size_dir) else { continue };
+                    for category in categories.flatten() {
+                        for ext in extensions {
+                            let candidate = category.path().join(format!("{name}.{ext}"));
+                            if candidate.is_file() {
+                                return Some(candidate.display().to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        extensions
+            .iter()
+            .map(|ext| PathBuf::from("/usr/share/pixmaps").join(format!("{name}.{ext}")))
+            .find(|p| p.is_file())
+            .map(|p| p.display().to_string())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = size;
+        None
+    }
+}
+
+/// Locate an installed `.desktop` file by desktop id. Ids map to paths
+/// with `-` standing for subdirectories ("kde-foo.desktop" may live at
+/// `applications/kde/foo.desktop`), searched in XDG data dir order.
+#[cfg(target_os = "linux")]
+fn find_desktop_file(desktop_id: &str) -> Option<PathBuf> {
+    let id = if desktop_id.ends_with(".desktop") { desktop_id.to_string() } else { format!("{desktop_id}.desktop") };
+    let mut dirs: Vec<PathBuf> = dirs::data_dir().into_iter().map(|d| d.join("applications")).collect();
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(data_dirs.split(':').filter(|d| !d.is_empty()).map(|d| PathBuf::from(d).join("applications")));
+
+    dirs.iter().find_map(|dir| {
+        let direct = dir.join(&id);
+        if direct.is_file() {
+            return Some(direct);
+        }
+        id.match_indices('-')
+            .map(|(i, _)| dir.join(&id[..i]).join(&id[i + 1..]))
+            .find(|p| p.is_file())
+    })
+}
+
+/// Icon file for an application: its desktop entry's `Icon=` resolved
+/// through the icon theme like `resolve_icon`. `None` when the app or its
+/// icon can't be found.
+pub fn resolve_app_icon(desktop_id: &str, size: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let entry = std::fs::read_to_string(find_desktop_file(desktop_id)?).ok()?;
+        let icon = entry
+            .lines()
+            .skip_while(|l| l.trim() != "[Desktop Entry]")
+            .skip(1)
+            .take_while(|l| !l.starts_with('['))
+            .find_map(|l| l.strip_prefix("Icon="))?
+            .trim();
+        if icon.is_empty() {
+            return None;
+        }
+        resolve_icon(icon, size)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (desktop_id, size);
+        None
+    }
+}
+
+/// Input devices
+
+/// X11 input devices as (id, name) pairs.
+#[cfg(target_os = "linux")]
+fn xinput_devices() -> Result<Vec<(String, String)>, PlatformError> {
+    let ids = run_tool("xinput", &["list", "--id-only"])?;
+    let names = run_tool("xinput", &["list", "--name-only"])?;
+    Ok(ids
+        .lines()
+        .zip(names.lines())
+        .map(|(id, name)| (id.trim().to_string(), name.trim().to_string()))
+        .collect())
+}
+
+/// X11 devices whose name marks them as a touchpad: (id, name).
+#[cfg(target_os = "linux")]
+fn xinput_touchpads() -> Result<Vec<(String, String)>, PlatformError> {
+    Ok(xinput_devices()?
+        .into_iter()
+        .filter(|(_, name)| {
+            let lower = name.to_lowercase();
+            lower.contains("touchpad") || lower.contains("trackpad")
+        })
+        .collect())
+}
+
+/// Pick the touchpad to act on: the one matching `selector` (id or name
+/// substring), or the only one present.
+#[cfg(target_os = "linux")]
+fn select_touchpad(selector: Option<&str>) -> Result<String, PlatformError> {
+    let pads = xinput_touchpads()?;
+    let matching: Vec<&(String, String)> = match selector {
+        Some(sel) => pads
+            .iter()
+            .filter(|(id, name)| id == sel || name.to_lowercase().contains(&sel.to_lowercase()))
+            .collect(),
+        None => pads.iter().collect(),
+    };
+    match matching.as_slice() {
+        [] => Err(PlatformError::Unsupported("no touchpad found".into())),
+        [(id, _)] => Ok(id.clone()),
+        many => Err(PlatformError::Msg(format!(
+            "Multiple touchpads found, pass a device: {}",
+            many.iter().map(|(id, name)| format!("{name} (id={id})")).collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
+
+/// Whether the touchpad is enabled. GNOME uses the `send-events` setting
+/// (applied by libinput for every touchpad); X11 falls back to `xinput`.
+pub fn get_touchpad_enabled(device: Option<&str>) -> Result<bool, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("gnome") && device.is_none() {
+            let value = run_tool(
+                "gsettings",
+                &["get", "org.gnome.desktop.peripherals.touchpad", "send-events"],
+            )?;
+            return Ok(value.trim_matches('\'') != "disabled");
+        }
+        if is_wayland() {
+            return Err(PlatformError::Unsupported("no touchpad backend for this Wayland session".into()));
+        }
+        let id = select_touchpad(device)?;
+        let props = run_tool("xinput", &["list-props", &id])?;
+        let enabled = props
+            .lines()
+            .find(|l| l.trim_start().starts_with("Device Enabled"))
+            .and_then(|l| l.rsplit(':').next())
+            .map(|v| v.trim() == "1")
+            .ok_or_else(|| PlatformError::Msg("xinput did not report 'Device Enabled'".into()))?;
+        Ok(enabled)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = device;
+        Err(PlatformError::Unsupported("touchpad control is only available on Linux".into()))
+    }
+}
+
+pub fn set_touchpad_enabled(enabled: bool, device: Option<&str>) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("gnome") && device.is_none() {
+            let value = if enabled { "enabled" } else { "disabled" };
+            return run_tool(
+                "gsettings",
+                &["set", "org.gnome.desktop.peripherals.touchpad", "send-events", value],
+            )
+            .map(|_| ());
+        }
+        if is_wayland() {
+            return Err(PlatformError::Unsupported("no touchpad backend for this Wayland session".into()));
+        }
+        let id = select_touchpad(device)?;
+        let value = if enabled { "1" } else { "0" };
+        run_tool("xinput", &["set-prop", &id, "Device Enabled", value]).map(|_| ())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (enabled, device);
+        Err(PlatformError::Unsupported("touchpad control is only available on Linux".into()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct KeyRepeat {
+    /// Delay before a held key starts repeating
+    pub delay_ms: u32,
+    /// Repeats per second once it does
+    pub rate_hz: u32,
+}
+
+/// Parse a `gsettings get` uint32 such as "uint32 500".
+#[cfg(target_os = "linux")]
+fn gsettings_u32(schema: &str, key: &str) -> Result<u32, PlatformError> {
+    let raw = run_tool("gsettings", &["get", schema, key])?;
+    raw.split_whitespace()
+        .last()
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| PlatformError::Msg(format!("unexpected gsettings value for {key}: {raw}")))
+}
+
+/// Keyboard repeat settings: GNOME's gsettings (X11 and Wayland), else
+/// `xset q` on X11.
+pub fn get_key_repeat() -> Result<KeyRepeat, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("gnome") {
+            let schema = "org.gnome.desktop.peripherals.keyboard";
+            let delay_ms = gsettings_u32(schema, "delay")?;
+            let interval = gsettings_u32(schema, "repeat-interval")?.max(1);
+            return Ok(KeyRepeat { delay_ms, rate_hz: 1000 / interval });
+        }
+        if is_wayland() {
+            return Err(PlatformError::Unsupported("no key repeat backend for this Wayland session".into()));
+        }
+        // "  auto repeat delay:  660    repeat rate:  25"
+        let out = run_tool("xset", &["q"])?;
+        let line = out
+            .lines()
+            .find(|l| l.contains("auto repeat delay:"))
+            .ok_or_else(|| PlatformError::Msg("xset q reported no repeat settings".into()))?;
+        let numbers: Vec<u32> = line
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|n| n.parse().ok())
+            .collect();
+        match numbers[..] {
+            [delay_ms, rate_hz, ..] => Ok(KeyRepeat { delay_ms, rate_hz }),
+            _ => Err(PlatformError::Msg(format!("unexpected xset output: {}", line.trim()))),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("key repeat control is only available on Linux".into()))
+    }
+}
+
+/// Set the repeat delay (clamped to 100–2000 ms) and rate (1–100 Hz).
+/// Returns the values applied.
+pub fn set_key_repeat(delay_ms: u32, rate_hz: u32) -> Result<KeyRepeat, PlatformError> {
+    let applied = KeyRepeat { delay_ms: delay_ms.clamp(100, 2000), rate_hz: rate_hz.clamp(1, 100) };
+
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("gnome") {
+            let schema = "org.gnome.desktop.peripherals.keyboard";
+            run_tool("gsettings", &["set", schema, "repeat", "true"])?;
+            run_tool("gsettings", &["set", schema, "delay", &applied.delay_ms.to_string()])?;
+            let interval = (1000 / applied.rate_hz).to_string();
+            run_tool("gsettings", &["set", schema, "repeat-interval", &interval])?;
+            return Ok(applied);
+        }
+        if is_wayland() {
+            return Err(PlatformError::Unsupported("no key repeat backend for this Wayland session".into()));
+        }
+        let delay = applied.delay_ms.to_string();
+        let rate = applied.rate_hz.to_string();
+        run_tool("xset", &["r", "rate", &delay, &rate])?;
+        Ok(applied)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = applied;
+        Err(PlatformError::Unsupported("key repeat control is only available on Linux".into()))
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InputDevice {
+    /// xinput id on X11 (usable as a touchpad `device`), else the evdev
+    /// node, e.g. "event3"
+    pub id: String,
+    pub name: String,
+    /// "keyboard" | "pointer" | "touchpad" | "touchscreen" | "other"
+    pub kind: String,
+    /// Always true outside X11, where there's no per-device toggle to read
+    pub enabled: bool,
+}
+
+/// Keyboards, mice, touchpads and touchscreens. X11 lists devices through
+/// `xinput` (with their enabled state); elsewhere `/proc/bus/input/devices`
+/// is read and classified with udev's `ID_INPUT_*` tags when the udev
+/// database is readable. Anything that can't be enumerated is left out.
+pub fn list_input_devices() -> Vec<InputDevice> {
+    #[cfg(target_os = "linux")]
+    {
+        let name_kind = |name: &str| {
+            let lower = name.to_lowercase();
+            if lower.contains("touchpad") || lower.contains("trackpad") {
+                Some("touchpad")
+            } else if lower.contains("touchscreen") {
+                Some("touchscreen")
+            } else {
+                None
+            }
+        };
+
+        if !is_wayland() {
+            if let (Ok(devices), Ok(listing)) = (xinput_devices(), run_tool("xinput", &["list"])) {
+                return devices
+                    .into_iter()
+                    .filter_map(|(id, name)| {
+                        // "↳ Name   id=12	[slave  pointer  (2)]"
+                        let line = listing.lines().find(|l| l.contains(&format!("id={id}\t")))?;
+                        if !line.contains("slave") || name.contains("XTEST") {
+                            return None;
+                        }
+                        let kind = name_kind(&name).unwrap_or(if line.contains("keyboard") {
+                            "keyboard"
+                        } else if line.contains("pointer") {
+                            "pointer"
+                        } else {
+                            "other"
+                        });
+                        let enabled = run_tool("xinput", &["list-props", &id])
+                            .ok()
+                            .and_then(|props| xinput_prop(&props, "Device Enabled"))
+                            .map_or(true, |v| v.first().is_some_and(|x| x == "1"));
+                        Some(InputDevice { id, name, kind: kind.into(), enabled })
+                    })
+                    .collect();
+            }
+        }
+
+        let Ok(raw) = std::fs::read_to_string("/proc/bus/input/devices") else {
+            return Vec::new();
+        };
+        raw.split("\n\n")
+            .filter_map(|block| {
+                let name = block.lines().find_map(|l| l.strip_prefix("N: Name="))?.trim_matches('"').to_string();
+                let handlers = block.lines().find_map(|l| l.strip_prefix("H: Handlers="))?;
+                let event = handlers.split_whitespace().find(|h| h.starts_with("event"))?.to_string();
+                let minor: u32 = event.trim_start_matches("event").parse().ok()?;
+                // udev's db for the evdev node (char major 13, minor 64 + N)
+                let tags = std::fs::read_to_string(format!("/run/udev/data/c13:{}", 64 + minor)).unwrap_or_default();
+                let tagged = |tag: &str| tags.lines().any(|l| l == format!("E:{tag}=1"));
+                let kind = if tagged("ID_INPUT_TOUCHPAD") {
+                    "touchpad"
+                } else if tagged("ID_INPUT_TOUCHSCREEN") {
+                    "touchscreen"
+                } else if tagged("ID_INPUT_MOUSE") || tagged("ID_INPUT_POINTINGSTICK") {
+                    "pointer"
+                } else if tagged("ID_INPUT_KEYBOARD") {
+                    "keyboard"
+                } else if let Some(kind) = name_kind(&name) {
+                    kind
+                } else if handlers.contains("mouse") {
+                    "pointer"
+                } else if handlers.contains("kbd") && name.to_lowercase().contains("keyboard") {
+                    "keyboard"
+                } else {
+                    "other"
+                };
+                Some(InputDevice { id: event, name, kind: kind.into(), enabled: true })
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct GestureSettings {
+    pub tap_to_click: bool,
+    pub two_finger_scroll: bool,
+    pub edge_scroll: bool,
+    pub natural_scroll: bool,
+    pub disable_while_typing: bool,
+}
+
+/// Fields left `None` are not touched.
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+pub struct GestureUpdate {
+    pub tap_to_click: Option<bool>,
+    pub two_finger_scroll: Option<bool>,
+    pub edge_scroll: Option<bool>,
+    pub natural_scroll: Option<bool>,
+    pub disable_while_typing: Option<bool>,
+}
+
+/// GNOME touchpad keys, in `GestureSettings` field order.
+#[cfg(target_os = "linux")]
+const GNOME_GESTURE_KEYS: [&str; 5] = [
+    "tap-to-click",
+    "two-finger-scrolling-enabled",
+    "edge-scrolling-enabled",
+    "natural-scroll",
+    "disable-while-typing",
+];
+
+/// Values of an `xinput list-props` property, e.g.
+/// `libinput Scroll Method Enabled (301):	1, 0, 0` -> ["1", "0", "0"].
+#[cfg(target_os = "linux")]
+fn xinput_prop(props: &str, name: &str) -> Option<Vec<String>> {
+    props
+        .lines()
+        .find(|l| l.trim_start().starts_with(&format!("{name} (")))
+        .and_then(|l| l.split_once("):"))
+        .map(|(_, values)| values.split(',').map(|v| v.trim().to_string()).collect())
+}
+
+/// KWin's `org.kde.KWin.InputDevice` properties, in `GestureSettings` field
+/// order. The X11 touchpad KCM stores its `touchpadxlibinputrc` keys under
+/// the same names.
+#[cfg(target_os = "linux")]
+const KWIN_GESTURE_PROPS: [&str; 5] = ["tapToClick", "scrollTwoFinger", "scrollEdge", "naturalScroll", "disableWhileTyping"];
+
+#[cfg(target_os = "linux")]
+fn kwin_device_prop(path: &str, property: &str) -> Result<String, PlatformError> {
+    dbus_get_property("--session", "org.kde.KWin", path, "org.kde.KWin.InputDevice", property)
+}
+
+/// Number in a GVariant reply such as `(<uint32 1267>,)`.
+#[cfg(target_os = "linux")]
+fn gvariant_u32(reply: &str) -> Option<u32> {
+    reply.rsplit(' ').next()?.trim_end_matches(|c: char| !c.is_ascii_digit()).parse().ok()
+}
+
+/// D-Bus object path of the touchpad KWin manages (Wayland sessions),
+/// e.g. `/org/kde/KWin/InputDevice/event5`.
+#[cfg(target_os = "linux")]
+fn kwin_touchpad() -> Result<String, PlatformError> {
+    let names = dbus_get_property(
+        "--session",
+        "org.kde.KWin",
+        "/org/kde/KWin/InputDevice",
+        "org.kde.KWin.InputDeviceManager",
+        "devicesSysNames",
+    )?;
+    let pads: Vec<String> = gvariant_strings(&names)
+        .into_iter()
+        .map(|sys| format!("/org/kde/KWin/InputDevice/{sys}"))
+        .filter(|path| kwin_device_prop(path, "touchpad").is_ok_and(|v| v.contains("true")))
+        .collect();
+    match pads.as_slice() {
+        [] => Err(PlatformError::Unsupported("no touchpad found".into())),
+        [path] => Ok(path.clone()),
+        many => Err(PlatformError::Msg(format!("Multiple touchpads found: {}", many.join(", ")))),
+    }
+}
+
+/// Persist KWin touchpad settings to `kcminputrc`, where KWin reads them
+/// back at login (`[Libinput][vendor][product][name]`). Only one scroll
+/// method can be active, stored as libinput's bit: 1 two-finger, 2 edge.
+#[cfg(target_os = "linux")]
+fn kwin_persist_gestures(path: &str, settings: &GestureSettings) -> Result<(), PlatformError> {
+    let vendor = kwin_device_prop(path, "vendor")?;
+    let product = kwin_device_prop(path, "product")?;
+    let name = kwin_device_prop(path, "name")?;
+    let (Some(vendor), Some(product), Some(name)) =
+        (gvariant_u32(&vendor), gvariant_u32(&product), gvariant_strings(&name).into_iter().next())
+    else {
+        return Err(PlatformError::Msg(format!("KWin did not identify touchpad {path}")));
+    };
+    let scroll_method = if settings.two_finger_scroll {
+        "1"
+    } else if settings.edge_scroll {
+        "2"
+    } else {
+        "0"
+    };
+    let (vendor, product) = (vendor.to_string(), product.to_string());
+    let bool_str = |v: bool| if v { "true" } else { "false" };
+    for (key, value) in [
+        ("TapToClick", bool_str(settings.tap_to_click)),
+        ("ScrollMethod", scroll_method),
+        ("NaturalScroll", bool_str(settings.natural_scroll)),
+        ("DisableWhileTyping", bool_str(settings.disable_while_typing)),
+    ] {
+        run_tool(
+            kde_config_tool(true),
+            &[
+                "--file", "kcminputrc", "--group", "Libinput", "--group", &vendor, "--group", &product,
+                "--group", &name, "--key", key, value,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Touchpad gesture settings: GNOME's gsettings, KWin's input device
+/// properties on KDE Wayland, else the libinput properties of the X11
+/// touchpad. Other Wayland sessions are unsupported.
+pub fn get_gesture_settings() -> Result<GestureSettings, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let values: [bool; 5] = if desktop_is("gnome") {
+            let mut values = [false; 5];
+            for (value, key) in values.iter_mut().zip(GNOME_GESTURE_KEYS) {
+                *value = run_tool("gsettings", &["get", "org.gnome.desktop.peripherals.touchpad", key])? == "true";
+            }
+            values
+        } else if desktop_is("kde") && is_wayland() {
+            let path = kwin_touchpad()?;
+            let mut values = [false; 5];
+            for (value, prop) in values.iter_mut().zip(KWIN_GESTURE_PROPS) {
+                *value = kwin_device_prop(&path, prop)?.contains("true");
+            }
+            values
+        } else if is_wayland() {
+            return Err(PlatformError::Unsupported("no touchpad backend for this Wayland session".into()));
+        } else {
+            let props = run_tool("xinput", &["list-props", &select_touchpad(None)?])?;
+            let flag = |name: &str, index: usize| {
+                xinput_prop(&props, name).and_then(|v| v.get(index).map(|x| x == "1")).unwrap_or(false)
+            };
+            [
+                flag("libinput Tapping Enabled", 0),
+                flag("libinput Scroll Method Enabled", 0),
+                flag("libinput Scroll Method Enabled", 1),
+                flag("libinput Natural Scrolling Enabled", 0),
+                flag("libinput Disable While Typing Enabled", 0),
+            ]
+        };
+        let [tap_to_click, two_finger_scroll, edge_scroll, natural_scroll, disable_while_typing] = values;
+        Ok(GestureSettings { tap_to_click, two_finger_scroll, edge_scroll, natural_scroll, disable_while_typing })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("touchpad control is only available on Linux".into()))
+    }
+}
+
+/// Apply the given fields and return the resulting settings. On KDE the
+/// result is also saved where Plasma re-applies it at login: `kcminputrc`
+/// on Wayland, the touchpad KCM's `touchpadxlibinputrc` on X11.
+pub fn set_gesture_settings(update: GestureUpdate) -> Result<GestureSettings, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let requested = [
+            update.tap_to_click,
+            update.two_finger_scroll,
+            update.edge_scroll,
+            update.natural_scroll,
+            update.disable_while_typing,
+        ];
+        if desktop_is("gnome") {
+            for (value, key) in requested.iter().zip(GNOME_GESTURE_KEYS) {
+                if let Some(value) = value {
+                    let value = if *value { "true" } else { "false" };
+                    run_tool("gsettings", &["set", "org.gnome.desktop.peripherals.touchpad", key, value])?;
+                }
+            }
+            return get_gesture_settings();
+        }
+        if desktop_is("kde") && is_wayland() {
+            let path = kwin_touchpad()?;
+            for (value, prop) in requested.iter().zip(KWIN_GESTURE_PROPS) {
+                if let Some(value) = value {
+                    let variant = if *value { "<true>" } else { "<false>" };
+                    run_tool(
+                        "gdbus",
+                        &[
+                            "call", "--session", "--dest", "org.kde.KWin", "--object-path", &path,
+                            "--method", "org.freedesktop.DBus.Properties.Set", "org.kde.KWin.InputDevice", prop, variant,
+                        ],
+                    )?;
+                }
+            }
+            let applied = get_gesture_settings()?;
+            kwin_persist_gestures(&path, &applied)?;
+            return Ok(applied);
+        }
+        if is_wayland() {
+            return Err(PlatformError::Unsupported("no touchpad backend for this Wayland session".into()));
+        }
+
+        let id = select_touchpad(None)?;
+        let set = |name: &str, values: &[&str]| -> Result<(), PlatformError> {
+            let mut args = vec!["set-prop", id.as_str(), name];
+            args.extend_from_slice(values);
+            run_tool("xinput", &args).map(|_| ())
+        };
+        let bit = |v: bool| if v { "1" } else { "0" };
+
+        if let Some(v) = update.tap_to_click {
+            set("libinput Tapping Enabled", &[bit(v)])?;
+        }
+        if update.two_finger_scroll.is_some() || update.edge_scroll.is_some() {
+            // The scroll method is one property: two-finger, edge, button.
+            let props = run_tool("xinput", &["list-props", &id])?;
+            let current = xinput_prop(&props, "libinput Scroll Method Enabled")
+                .ok_or_else(|| PlatformError::Unsupported("touchpad has no libinput scroll methods".into()))?;
+            let mut methods: Vec<&str> = current.iter().map(String::as_str).collect();
+            if let (Some(v), Some(slot)) = (update.two_finger_scroll, methods.get_mut(0)) {
+                *slot = bit(v);
+            }
+            if let (Some(v), Some(slot)) = (update.edge_scroll, methods.get_mut(1)) {
+                *slot = bit(v);
+            }
+            set("libinput Scroll Method Enabled", &methods)?;
+        }
+        if let Some(v) = update.natural_scroll {
+            set("libinput Natural Scrolling Enabled", &[bit(v)])?;
+        }
+        if let Some(v) = update.disable_while_typing {
+            set("libinput Disable While Typing Enabled", &[bit(v)])?;
+        }
+        let applied = get_gesture_settings()?;
+        if desktop_is("kde") {
+            let name = xinput_touchpads()?
+                .into_iter()
+                .find_map(|(pad, name)| (pad == id).then_some(name))
+                .ok_or_else(|| PlatformError::Msg(format!("touchpad {id} disappeared")))?;
+            let values = [
+                applied.tap_to_click,
+                applied.two_finger_scroll,
+                applied.edge_scroll,
+                applied.natural_scroll,
+                applied.disable_while_typing,
+            ];
+            for (value, key) in values.iter().zip(KWIN_GESTURE_PROPS) {
+                let value = if *value { "true" } else { "false" };
+                run_tool(
+                    kde_config_tool(true),
+                    &["--file", "touchpadxlibinputrc", "--group", &name, "--key", key, value],
+                )?;
+            }
+        }
+        Ok(applied)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = update;
+        Err(PlatformError::Unsupported("touchpad control is only available on Linux".into()))
+    }
+}
+
+/// Notifications
+
+/// Show a desktop notification and return its server id. `urgency` is
+/// "low" | "normal" | "critical" (critical bypasses do-not-disturb on daemons
+/// that honor it); `timeout_ms` is -1 for the server default, 0 for never.
+pub fn notify(
+    title: &str,
+    body: &str,
+    urgency: Option<&str>,
+    timeout_ms: Option<i32>,
+) -> Result<u32, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        use notify_rust::{Notification, Timeout, Urgency};
+
+        let urgency = match urgency.unwrap_or("normal") {
+            "low" => Urgency::Low,
+            "normal" => Urgency::Normal,
+            "critical" => Urgency::Critical,
+            other => return Err(PlatformError::Msg(format!("Unknown urgency '{other}'"))),
+        };
+        let timeout = match timeout_ms.unwrap_or(-1) {
+            t if t < 0 => Timeout::Default,
+            0 => Timeout::Never,
+            t => Timeout::Milliseconds(t as u32),
+        };
+        let handle = Notification::new()
+            .summary(title)
+            .body(body)
+            .urgency(urgency)
+            .timeout(timeout)
+            .show()
+            .map_err(|e| PlatformError::Msg(format!("notify failed: {e}")))?;
+        Ok(handle.id())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (title, body, urgency, timeout_ms);
+        Err(PlatformError::Unsupported("notifications are only available on Linux".into()))
+    }
+}
+
+/// Dismiss a notification that is still showing.
+pub fn close_notification(id: u32) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        run_tool(
+            "gdbus",
+            &[
+                "call", "--session",
+                "--dest", "org.freedesktop.Notifications",
+                "--object-path", "/org/freedesktop/Notifications",
+                "--method", "org.freedesktop.Notifications.CloseNotification",
+                &id.to_string(),
+            ],
+        )
+        .map(|_| ())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = id;
+        Err(PlatformError::Unsupported("notifications are only available on Linux".into()))
+    }
+}
+
+/// Keyboard backlight
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct KbdBacklight {
+    pub brightness: u32,
+    pub max: u32,
+}
+
+/// The first `/sys/class/leds/*kbd_backlight*` device.
+#[cfg(target_os = "linux")]
+fn kbd_backlight_dir() -> Result<PathBuf, PlatformError> {
+    std::fs::read_dir("/sys/class/leds")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .find(|e| e.file_name().to_string_lossy().contains("kbd_backlight"))
+        .map(|e| e.path())
+        .ok_or_else(|| PlatformError::Unsupported("no keyboard backlight found".into()))
+}
+
+pub fn get_kbd_backlight() -> Result<KbdBacklight, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let dir = kbd_backlight_dir()?;
+        let read = |f: &str| -> Result<u32, PlatformError> {
+            let raw = std::fs::read_to_string(dir.join(f))?;
+            raw.trim().parse().map_err(|_| PlatformError::Msg(format!("Unexpected {f} value '{}'", raw.trim())))
+        };
+        Ok(KbdBacklight { brightness: read("brightness")?, max: read("max_brightness")? })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("keyboard backlight is only available on Linux".into()))
+    }
+}
+
+/// Set the keyboard backlight level (clamped to `max`). Writes sysfs
+/// directly, falling back to logind's `SetBrightness`, which lets the active
+/// session change LEDs without a udev rule. Returns the applied level.
+pub fn set_kbd_backlight(brightness: u32) -> Result<u32, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let dir = kbd_backlight_dir()?;
+        let max = get_kbd_backlight()?.max;
+        let level = brightness.min(max);
+        match sysfs_write(&dir.join("brightness"), &level.to_string()) {
+            Ok(()) => Ok(level),
+            Err(PlatformError::PermissionDenied(_)) => {
+                let name = dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                run_tool(
+                    "gdbus",
+                    &[
+                        "call", "--system",
+                        "--dest", "org.freedesktop.login1",
+                        "--object-path", "/org/freedesktop/login1/session/auto",
+                        "--method", "org.freedesktop.login1.Session.SetBrightness",
+                        "leds", &name, &level.to_string(),
+                    ],
+                )
+                .map(|_| level)
+                .map_err(|_| {
+                    PlatformError::PermissionDenied(format!(
+                        "cannot write {}/brightness; add a udev rule granting your user write access \
+                         (e.g. RUN+=\"/bin/chmod a+w /sys/class/leds/{name}/brightness\")",
+                        dir.display()
+                    ))
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = brightness;
+        Err(PlatformError::Unsupported("keyboard backlight is only available on Linux".into()))
+    }
+}
+
+/// Input methods
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InputMethodState {
+    /// "fcitx5" | "fcitx" | "ibus"
+    pub framework: String,
+    pub engine: String,
+    /// Composition is on (a non-keyboard-layout engine is active)
+    pub active: bool,
+}
+
+/// Active IME framework and engine. fcitx is queried through
+/// `fcitx5-remote`/`fcitx-remote`, ibus through `ibus engine`; both talk to
+/// the framework over D-Bus and fail when it isn't running.
+pub fn get_input_method() -> Result<InputMethodState, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        for remote in ["fcitx5-remote", "fcitx-remote"] {
+            // 0 = not running, 1 = inactive, 2 = active
+            if let Ok(state) = run_tool(remote, &[]) {
+                if state == "0" {
+                    continue;
+                }
+                return Ok(InputMethodState {
+                    framework: remote.trim_end_matches("-remote").to_string(),
+                    engine: run_tool(remote, &["-n"]).unwrap_or_default(),
+                    active: state == "2",
+                });
+            }
+        }
+        if let Ok(engine) = run_tool("ibus", &["engine"]) {
+            return Ok(InputMethodState {
+                framework: "ibus".into(),
+                active: !engine.starts_with("xkb:"),
+                engine,
+            });
+        }
+        Err(PlatformError::Unsupported("no input method framework is running".into()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("input method control is only available on Linux".into()))
+    }
+}
+
+/// Turn composition on or off. For ibus, "off" switches to the first
+/// keyboard-layout (`xkb:`) engine and "on" to the first preloaded IME.
+pub fn set_input_method_enabled(enabled: bool) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let current = get_input_method()?;
+        if current.framework != "ibus" {
+            let remote = format!("{}-remote", current.framework);
+            let flag = if enabled { "-o" } else { "-c" };
+            return run_tool(&remote, &[flag]).map(|_| ());
+        }
+
+        let preload = run_tool("gsettings", &["get", "org.freedesktop.ibus.general", "preload-engines"])
+            .unwrap_or_default();
+        let engines = gvariant_strings(&preload);
+        let target = if enabled {
+            engines.iter().find(|e| !e.starts_with("xkb:"))
+        } else {
+            engines.iter().find(|e| e.starts_with("xkb:"))
+        };
+        let target = target.ok_or_else(|| {
+            PlatformError::Msg(format!(
+                "No {} engine configured in ibus",
+                if enabled { "input method" } else { "keyboard layout" }
+            ))
+        })?;
+        run_tool("ibus", &["engine", target]).map(|_| ())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = enabled;
+        Err(PlatformError::Unsupported("input method control is only available on Linux".into()))
+    }
+}
+
+/// Lock keys
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct LockKeys {
+    pub caps: bool,
+    pub num: bool,
+    pub scroll: bool,
+}
+
+/// Lock-key state from the evdev LEDs in sysfs (works under X11 and
+/// Wayland), falling back to `xset q` on X11.
+pub fn get_lock_keys() -> Result<LockKeys, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut keys = LockKeys::default();
+        let mut found = false;
+        for entry in std::fs::read_dir("/sys/class/leds").into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let slot = match name.rsplit("::").next() {
+                Some("capslock") => &mut keys.caps,
+                Some("numlock") => &mut keys.num,
+                Some("scrolllock") => &mut keys.scroll,
+                _ => continue,
+            };
+            found = true;
+            let on = std::fs::read_to_string(entry.path().join("brightness"))
+                .map(|b| b.trim() != "0")
+                .unwrap_or(false);
+            // Any keyboard showing the LED lit counts.
+            *slot |= on;
+        }
+        if found {
+            return Ok(keys);
+        }
+        if !is_wayland() {
+            // "00: Caps Lock:   off    01: Num Lock:    on     02: Scroll Lock: off"
+            let query = run_tool("xset", &["q"])?;
+            let state = |label: &str| {
+                query
+                    .split(label)
+                    .nth(1)
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .map(|v| v == "on")
+                    .unwrap_or(false)
+            };
+            return Ok(LockKeys {
+                caps: state("Caps Lock:"),
+                num: state("Num Lock:"),
+                scroll: state("Scroll Lock:"),
+            });
+        }
+        Err(PlatformError::Unsupported("no lock-key LEDs found".into()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("lock-key state is only available on Linux".into()))
+    }
+}
+
+/// Turn a lock key ("caps" | "num" | "scroll") on or off by synthesizing
+/// a key press through XTEST (`xdotool`) when its state differs. Wayland
+/// compositors don't let clients change lock state.
+pub fn set_lock_key(key: &str, on: bool) -> Result<(), PlatformError> {
+    let keysym = match key {
+        "caps" => "Caps_Lock",
+        "num" => "Num_Lock",
+        "scroll" => "Scroll_Lock",
+        other => return Err(PlatformError::Msg(format!("Unknown lock key '{other}'"))),
+    };
+
+    #[cfg(target_os = "linux")]
+    {
+        if is_wayland() {
+            return Err(PlatformError::Unsupported("lock keys can't be set on Wayland".into()));
+        }
+        let keys = get_lock_keys()?;
+        let current = match key {
+            "caps" => keys.caps,
+            "num" => keys.num,
+            _ => keys.scroll,
+        };
+        if current != on {
+            run_tool("xdotool", &["key", keysym])?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (keysym, on);
+        Err(PlatformError::Unsupported("lock keys can only be set on Linux".into()))
+    }
+}
+
+/// Accessibility
+
+/// Whether the desktop asks for reduced motion. GNOME's `enable-animations`
+/// is inverted; KDE reports it through a zero `AnimationDurationFactor` in
+/// `kdeglobals`. Other desktops are asked through the settings portal's
+/// `org.freedesktop.appearance` `reduced-motion` key (xdg-desktop-portal
+/// 1.20+), which is read-only.
+pub fn get_reduced_motion() -> Result<bool, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("gnome") {
+            let value = run_tool("gsettings", &["get", "org.gnome.desktop.interface", "enable-animations"])?;
+            return Ok(value == "false");
+        }
+        if desktop_is("kde") {
+            let value = run_tool(
+                kde_config_tool(false),
+                &["--file", "kdeglobals", "--group", "KDE", "--key", "AnimationDurationFactor"],
+            )?;
+            return Ok(value.parse::<f64>().map(|f| f == 0.0).unwrap_or(false));
+        }
+        // "(<<uint32 1>>,)": 0 = no preference, 1 = reduce
+        let reply = run_tool(
+            "gdbus",
+            &[
+                "call", "--session",
+                "--dest", "org.freedesktop.portal.Desktop",
+                "--object-path", "/org/freedesktop/portal/desktop",
+                "--method", "org.freedesktop.portal.Settings.ReadOne",
+                "org.freedesktop.appearance", "reduced-motion",
+            ],
+        )
+        .map_err(|_| PlatformError::Unsupported("no motion preference backend found".into()))?;
+        let value = reply
+            .trim_end_matches(|c: char| !c.is_ascii_digit())
+            .rsplit(|c: char| !c.is_ascii_digit())
+            .next()
+            .and_then(|v| v.parse::<u32>().ok())
+            .ok_or_else(|| PlatformError::Msg(format!("Unexpected reduced-motion reply '{reply}'")))?;
+        Ok(value == 1)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("reduced motion is only available on Linux".into()))
+    }
+}
+
+/// Call `on_change(reduced)` from a background thread whenever the motion
+/// preference changes, including from system settings, until `stop` is set.
+/// GNOME is followed with `gsettings monitor`; elsewhere the settings
+/// portal's `SettingChanged` signal triggers a re-read (on KDE the portal
+/// forwards `kdeglobals` changes).
+pub fn watch_reduced_motion(
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    mut on_change: impl FnMut(bool) + Send + 'static,
+) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut last = get_reduced_motion()?;
+        let (program, args, trigger): (_, &[&str], _) = if desktop_is("gnome") {
+            ("gsettings", &["monitor", "org.gnome.desktop.interface", "enable-animations"], "enable-animations")
+        } else {
+            (
+                "gdbus",
+                &[
+                    "monitor", "--session",
+                    "--dest", "org.freedesktop.portal.Desktop",
+                    "--object-path", "/org/freedesktop/portal/desktop",
+                ],
+                "SettingChanged",
+            )
+        };
+        let args = args.iter().map(|a| a.to_string()).collect();
+        watch_tool_lines(program, args, stop, move |line| {
+            if !line.contains(trigger) {
+                return;
+            }
+            if let Ok(reduced) = get_reduced_motion() {
+                if reduced != last {
+                    last = reduced;
+                    on_change(reduced);
+                }
+            }
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (stop, &mut on_change);
+        Err(PlatformError::Unsupported("reduced motion is only available on Linux".into()))
+    }
+}
+
+/// Change the motion preference on GNOME or KDE. On KDE, KWin is told to
+/// reload and running apps get a `KGlobalSettings` change notification. The
+/// portal setting can't be written, so other desktops are unsupported.
+pub fn set_reduced_motion(enabled: bool) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if desktop_is("gnome") {
+            let animations = if enabled { "false" } else { "true" };
+            return run_tool("gsettings", &["set", "org.gnome.desktop.interface", "enable-animations", animations])
+                .map(|_| ());
+        }
+        if desktop_is("kde") {
+            let factor = if enabled { "0" } else { "1" };
+            run_tool(
+                kde_config_tool(true),
+                &["--file", "kdeglobals", "--group", "KDE", "--key", "AnimationDurationFactor", factor],
+            )?;
+            run_tool("gdbus", &["emit", "--session", "--object-path", "/KWin", "--signal", "org.kde.KWin.reloadConfig"])?;
+            // SettingsChanged (3), SETTINGS_STYLE (7)
+            run_tool(
+                "gdbus",
+                &["emit", "--session", "--object-path", "/KGlobalSettings", "--signal", "org.kde.KGlobalSettings.notifyChange", "3", "7"],
+            )?;
+            return Ok(());
+        }
+        Err(PlatformError::Unsupported("no writable motion preference backend for this desktop".into()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = enabled;
+        Err(PlatformError::Unsupported("reduced motion is only available on Linux".into()))
+    }
+}
+
+/// Keyring
+
+/// Map keyring errors, separating "no backend" (common in headless
+/// sessions without a Secret Service) from everything else. Messages never
+/// include the secret.
+fn keyring_error(e: keyring::Error) -> PlatformError {
+    match e {
+        keyring::Error::NoStorageAccess(_) | keyring::Error::PlatformFailure(_) => {
+            PlatformError::Unsupported(format!("no usable system keyring: {e}"))
+        }
+        other => PlatformError::Msg(format!("keyring error: {other}")),
+    }
+}
+
+pub fn keyring_set(service: &str, account: &str, secret: &str) -> Result<(), PlatformError> {
+    keyring::Entry::new(service, account)
+        .and_then(|entry| entry.set_password(secret))
+        .map_err(keyring_error)
+}
+
+/// The stored secret, or `None` when there is no entry.
+pub fn keyring_get(service: &str, account: &str) -> Result<Option<String>, PlatformError> {
+    match keyring::Entry::new(service, account).and_then(|entry| entry.get_password()) {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+/// Delete an entry; deleting a missing entry is not an error.
+pub fn keyring_delete(service: &str, account: &str) -> Result<(), PlatformError> {
+    match keyring::Entry::new(service, account).and_then(|entry| entry.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+/// Versions
+
+/// Parse a version string leniently: accepts a leading `v`/`V` and fills in
+/// missing minor/patch components (`"v1.2"` → `1.2.0`).
+pub fn parse_version(raw: &str) -> Result<semver::Version, PlatformError> {
+    let trimmed = raw.trim();
+    let trimmed = trimmed
+        .strip_prefix('v')
+        .or_else(|| trimmed.strip_prefix('V'))
+        .unwrap_or(trimmed);
+
+    // Split off any pre-release/build suffix so only the numeric core is padded.
+    let split_at = trimmed.find(['-', '+']).unwrap_or(trimmed.len());
+    let (core, suffix) = trimmed.split_at(split_at);
+    let mut parts: Vec<&str> = core.split('.').collect();
+    while parts.len() < 3 {
+        parts.push("0");
+    }
+    let normalized = format!("{}{}", parts.join("."), suffix);
+
+    semver::Version::parse(&normalized)
+        .map_err(|e| PlatformError::Msg(format!("Invalid version '{raw}': {e}")))
+}
+
+/// Compare two versions, returning -1, 0 or 1.
+pub fn compare_versions(a: &str, b: &str) -> Result<i8, PlatformError> {
+    let a = parse_version(a)?;
+    let b = parse_version(b)?;
+    Ok(match a.cmp(&b) {
+        std::cmp::Ordering::Less => -1,
+        std::cmp::Ordering::Equal => 0,
+        std::cmp::Ordering::Greater => 1,
+    })
+}
+
+/// Check whether `version` satisfies a requirement such as `">=1.2, <2"`.
+pub fn version_satisfies(version: &str, requirement: &str) -> Result<bool, PlatformError> {
+    let version = parse_version(version)?;
+    let req = semver::VersionReq::parse(requirement.trim())
+        .map_err(|e| PlatformError::Msg(format!("Invalid requirement '{requirement}': {e}")))?;
+    Ok(req.matches(&version))
+}
+
+/// Updates
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PendingUpdates {
+    /// "dnf" | "apt" | "pacman"
+    pub manager: String,
+    pub count: usize,
+    pub packages: Vec<String>,
+}
+
+/// `ID` followed by the `ID_LIKE` entries from os-release, e.g. ["ubuntu", "debian"].
+#[cfg(target_os = "linux")]
+fn os_release_ids() -> Vec<String> {
+    let raw = std::fs::read_to_string("/etc/os-release")
+        .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))
+        .unwrap_or_default();
+    let field = |key: &str| {
+        raw.lines()
+            .find_map(|l| l.strip_prefix(key))
+            .map(|v| v.trim_matches('"').to_string())
+            .unwrap_or_default()
+    };
+    let mut ids = vec![field("ID=")];
+    ids.extend(field("ID_LIKE=").split_whitespace().map(str::to_string));
+    ids.retain(|id| !id.is_empty());
+    ids
+}
+
+/// Packages with an update available, read-only (nothing is downloaded
+/// or installed). The package manager is picked from os-release.
+pub fn get_pending_updates() -> Result<PendingUpdates, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let ids = os_release_ids();
+        let is = |name: &str| ids.iter().any(|id| id == name);
+
+        let (manager, program, args): (&str, &str, &[&str]) = if is("fedora") || is("rhel") || is("centos") {
+            ("dnf", "dnf", &["check-update", "--quiet"])
+        } else if is("debian") || is("ubuntu") {
+            ("apt", "apt", &["list", "--upgradable"])
+        } else if is("arch") {
+            ("pacman", "checkupdates", &[])
+        } else {
+            return Err(PlatformError::Unsupported(format!(
+                "no known package manager for '{}'",
+                ids.first().map(String::as_str).unwrap_or("unknown")
+            )));
+        };
+
+        let output = Command::new(program).args(args).output().map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                PlatformError::Unsupported(format!("'{program}' is not installed"))
+            } else {
+                PlatformError::Io(e)
+            }
+        })?;
+        // dnf exits 100 when updates exist; checkupdates exits 2 when none do.
+        let code = output.status.code().unwrap_or(-1);
+        let ok = match manager {
+            "dnf" => code == 0 || code == 100,
+            "pacman" => code == 0 || code == 2,
+            _ => code == 0,
+        };
+        if !ok {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(PlatformError::Msg(format!("{program} failed: {}", stderr.trim())));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let packages: Vec<String> = match manager {
+            // "name.arch  version  repo"; stop at the obsoletes section
+            "dnf" => stdout
+                .lines()
+                .take_while(|l| !l.starts_with("Obsoleting"))
+                .filter(|l| l.split_whitespace().count() == 3)
+                .filter_map(|l| l.split_whitespace().next())
+                .map(|name| name.rsplit_once('.').map_or(name, |(n, _)| n).to_string())
+                .collect(),
+            // "name/suite version arch [upgradable from: old]"
+            "apt" => stdout
+                .lines()
+                .filter(|l| l.contains("[upgradable from"))
+                .filter_map(|l| l.split('/').next())
+                .map(str::to_string)
+                .collect(),
+            // "name old -> new"
+            _ => stdout
+                .lines()
+                .filter_map(|l| l.split_whitespace().next())
+                .map(str::to_string)
+                .collect(),
+        };
+
+        Ok(PendingUpdates { manager: manager.into(), count: packages.len(), packages })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("update checks are only implemented on Linux".into()))
+    }
+}
+
+/// Text files
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DecodedText {
+    pub contents: String,
+    /// WHATWG encoding name, e.g. "UTF-8", "windows-1252", "UTF-16LE"
+    pub detected_encoding: String,
+    /// The file started with a byte-order mark (stripped from `contents`)
+    pub had_bom: bool,
+    /// Some bytes didn't decode and were replaced with U+FFFD
+    pub had_errors: bool,
+}
+
+/// Read a file that must be UTF-8, failing with the offset of the first
+/// invalid byte otherwise.
+pub fn read_text_file(path: &str) -> Result<String, PlatformError> {
+    String::from_utf8(std::fs::read(path)?).map_err(|e| {
+        PlatformError::Msg(format!(
+            "'{path}' is not valid UTF-8 (invalid byte at offset {})",
+            e.utf8_error().valid_up_to()
+        ))
+    })
+}
+
+/// Read a text file in whatever encoding it's in. A BOM (UTF-8/UTF-16)
+/// wins; otherwise valid UTF-8 is taken as such and anything else goes
+/// through chardetng's guess (legacy single/multi-byte encodings — BOM-less
+/// UTF-16 isn't detected).
+pub fn read_text_file_detect(path: &str) -> Result<DecodedText, PlatformError> {
+    let bytes = std::fs::read(path)?;
+
+    let (encoding, body, had_bom) = match encoding_rs::Encoding::for_bom(&bytes) {
+        Some((encoding, bom_len)) => (encoding, &bytes[bom_len..], true),
+        None if std::str::from_utf8(&bytes).is_ok() => (encoding_rs::UTF_8, &bytes[..], false),
+        None => {
+            let mut detector = chardetng::EncodingDetector::new();
+            detector.feed(&bytes, true);
+            (detector.guess(None, true), &bytes[..], false)
+        }
+    };
+    let (contents, had_errors) = encoding.decode_without_bom_handling(body);
+
+    Ok(DecodedText {
+        contents: contents.into_owned(),
+        detected_encoding: encoding.name().to_string(),
+        had_bom,
+        had_errors,
+    })
+}
+
+/// Portals
+
+/// Permission-store (table, id) for each portal that keeps per-app grants.
+#[cfg(target_os = "linux")]
+const PORTAL_PERMISSIONS: &[(&str, &str, &str)] = &[
+    ("camera", "devices", "camera"),
+    ("microphone", "devices", "microphone"),
+    ("speakers", "devices", "speakers"),
+    ("location", "location", "location"),
+    ("notifications", "notifications", "notification"),
+    ("screenshot", "screenshot", "screenshot"),
+    ("background", "background", "background"),
+];
+
+/// Whether this app may use a portal: "granted" | "denied" | "ask" |
+/// "unknown". Only Flatpak gates access through the portal permission
+/// store, so unsandboxed (and AppImage) runs always get "granted"; Snap
+/// confinement can't be queried this way and reports "unknown". "ask"
+/// means no decision is stored yet and the portal will prompt.
+///
+/// Inside Flatpak the store is only reachable when the app is built with
+/// `--talk-name=org.freedesktop.impl.portal.PermissionStore` in its
+/// finish-args; without it this fails with `PermissionDenied` naming the
+/// missing finish-arg rather than guessing.
+pub fn check_portal_permission(portal: &str) -> Result<&'static str, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let &(_, table, id) = PORTAL_PERMISSIONS
+            .iter()
+            .find(|(name, _, _)| *name == portal)
+            .ok_or_else(|| PlatformError::Msg(format!("Unknown portal '{portal}'")))?;
+
+        match sandbox_kind() {
+            Some("flatpak") => {}
+            Some("snap") => return Ok("unknown"),
+            _ => return Ok("granted"),
+        }
+        let Ok(app_id) = std::env::var("FLATPAK_ID") else {
+            return Ok("unknown");
+        };
+
+        let reply = match run_tool(
+            "gdbus",
+            &[
+                "call", "--session",
+                "--dest", "org.freedesktop.impl.portal.PermissionStore",
+                "--object-path", "/org/freedesktop/impl/portal/PermissionStore",
+                "--method", "org.freedesktop.impl.portal.PermissionStore.Lookup", table, id,
+            ],
+        ) {
+            Ok(reply) => reply,
+            // The table/entry only exists once some app has been asked.
+            Err(PlatformError::Msg(e)) if e.contains("NotFound") => return Ok("ask"),
+            // The Flatpak D-Bus proxy hides names the app may not talk to.
+            Err(PlatformError::Msg(e))
+                if e.contains("ServiceUnknown") || e.contains("NameHasNoOwner") || e.contains("AccessDenied") =>
+            {
+                return Err(PlatformError::PermissionDenied(
+                    "the portal permission store is not reachable from the sandbox; add \
+                     --talk-name=org.freedesktop.impl.portal.PermissionStore to the Flatpak finish-args"
+                        .into(),
+                ));
+            }
+            Err(e) => return Err(e),
+        };
+
+        // ({'org.example.App': ['yes']}, <...>)
+        let strings = gvariant_strings(&reply);
+        let Some(value) = strings.iter().position(|s| *s == app_id).and_then(|i| strings.get(i + 1)) else {
+            return Ok("ask");
+        };
+        Ok(match value.as_str() {
+            "yes" => "granted",
+            "no" | "NONE" => "denied",
+            "ask" => "ask",
+            // location stores an accuracy level instead of yes/no
+            "COUNTRY" | "CITY" | "NEIGHBORHOOD" | "STREET" | "EXACT" => "granted",
+            _ => "unknown",
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = portal;
+        Ok("granted")
+    }
+}
+
+/// Window manager
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WmCapabilities {
+    /// "x11" | "wayland"
+    pub backend: String,
+    /// `_NET_*` atoms from `_NET_SUPPORTED` on X11; advertised global
+    /// interfaces (e.g. "xdg_wm_base") on Wayland
+    pub supported: Vec<String>,
+    /// Why the list may be incomplete
+    pub note: Option<String>,
+}
+
+/// What the window manager / compositor says it supports, so window
+/// features can be offered only where they'll work. On Wayland the globals
+/// come from `wayland-info` when installed; otherwise the list is empty.
+pub fn get_wm_capabilities() -> Result<WmCapabilities, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        if is_wayland() {
+            return Ok(match run_tool("wayland-info", &[]) {
+                Ok(out) => {
+                    // "interface: 'xdg_wm_base',   version:  5, name: 1"
+                    let mut supported: Vec<String> = out
+                        .lines()
+                        .filter(|l| l.contains("interface: '"))
+                        .filter_map(|l| gvariant_strings(l).into_iter().next())
+                        .collect();
+                    supported.sort();
+                    supported.dedup();
+                    WmCapabilities {
+                        backend: "wayland".into(),
+                        supported,
+                        note: Some("Wayland globals only; window-management actions are up to the compositor".into()),
+                    }
+                }
+                Err(_) => WmCapabilities {
+                    backend: "wayland".into(),
+                    supported: Vec::new(),
+                    note: Some("Wayland has no EWMH; install wayland-info to list compositor protocols".into()),
+                },
+            });
+        }
+
+        // "_NET_SUPPORTED(ATOM) = _NET_WM_NAME, _NET_WM_STATE, ..."
+        let out = run_tool("xprop", &["-root", "_NET_SUPPORTED"])?;
+        let Some((_, atoms)) = out.split_once('=') else {
+            return Ok(WmCapabilities {
+                backend: "x11".into(),
+                supported: Vec::new(),
+                note: Some("the window manager does not advertise _NET_SUPPORTED".into()),
+            });
+        };
+        Ok(WmCapabilities {
+            backend: "x11".into(),
+            supported: atoms.split(',').map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect(),
+            note: None,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("window manager introspection is only available on Linux".into()))
+    }
+}
+
+/// Clipboard
+
+/// Run a clipboard tool, treating "nothing on the clipboard" (a non-zero
+/// exit from wl-paste/xclip) as empty output.
+#[cfg(target_os = "linux")]
+fn clipboard_tool(program: &str, args: &[&str]) -> Result<Vec<u8>, PlatformError> {
+    let output = Command::new(program).args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            PlatformError::Unsupported(format!("'{program}' is not installed"))
+        } else {
+            PlatformError::Io(e)
+        }
+    })?;
+    Ok(if output.status.success() { output.stdout } else { Vec::new() })
+}
+
+/// MIME types (and X11 targets) currently offered on the clipboard, or the
+/// primary selection with `primary`. Uses `wl-paste` on Wayland and `xclip`
+/// on X11; X11 bookkeeping targets (TARGETS, TIMESTAMP, …) are left out.
+pub fn clipboard_available_formats(primary: bool) -> Result<Vec<String>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let raw = if is_wayland() {
+            let mut args = vec!["--list-types"];
+            if primary {
+                args.push("--primary");
+            }
+            clipboard_tool("wl-paste", &args)?
+        } else {
+            let selection = if primary { "primary" } else { "clipboard" };
+            clipboard_tool("xclip", &["-selection", selection, "-o", "-t", "TARGETS"])?
+        };
+        let mut formats: Vec<String> = Vec::new();
+        for line in String::from_utf8_lossy(&raw).lines().map(str::trim) {
+            let meta = matches!(line, "TARGETS" | "TIMESTAMP" | "MULTIPLE" | "SAVE_TARGETS" | "DELETE");
+            if !line.is_empty() && !meta && !formats.iter().any(|f| f == line) {
+                formats.push(line.to_string());
+            }
+        }
+        Ok(formats)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = primary;
+        Err(PlatformError::Unsupported("clipboard format listing is only implemented on Linux".into()))
+    }
+}
+
+/// Paths on the clipboard from a file manager's copy/cut. Reads GNOME's
+/// `x-special/gnome-copied-files` ("copy"/"cut" then one URI per line) when
+/// offered, else `text/uri-list`. Non-`file:` URIs are skipped.
+pub fn clipboard_read_files() -> Result<Vec<String>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let formats = clipboard_available_formats(false)?;
+        let read = |mime: &str| -> Result<String, PlatformError> {
+            let raw = if is_wayland() {
+                clipboard_tool("wl-paste", &["--no-newline", "--type", mime])?
+            } else {
+                clipboard_tool("xclip", &["-selection", "clipboard", "-o", "-t", mime])?
+            };
+            Ok(String::from_utf8_lossy(&raw).into_owned())
+        };
+
+        let uris: Vec<String> = if formats.iter().any(|f| f == "x-special/gnome-copied-files") {
+            // First line is the "copy" / "cut" action
+            read("x-special/gnome-copied-files")?.lines().skip(1).map(str::to_string).collect()
+        } else if formats.iter().any(|f| f == "text/uri-list") {
+            read("text/uri-list")?.lines().filter(|l| !l.starts_with('#')).map(str::to_string).collect()
+        } else {
+            return Ok(Vec::new());
+        };
+
+        Ok(uris
+            .iter()
+            .map(|u| u.trim())
+            .filter(|u| !u.is_empty())
+            .filter_map(|u| uri_to_path(u).ok())
+            .collect())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("clipboard file lists are only implemented on Linux".into()))
+    }
+}
+
+/// Put files on the clipboard for pasting in a file manager. With `cut`,
+/// GNOME's `x-special/gnome-copied-files` is used so Nautilus (and Nemo,
+/// Caja) move instead of copy; otherwise `text/uri-list`, which every file
+/// manager understands. wl-copy/xclip offer a single type per selection, so
+/// a cut only round-trips with GNOME-family file managers.
+pub fn clipboard_write_files(paths: &[String], cut: bool) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        if paths.is_empty() {
+            return Err(PlatformError::Msg("no files to put on the clipboard".into()));
+        }
+        let uris = paths.iter().map(|p| path_to_uri(p)).collect::<Result<Vec<_>, _>>()?;
+        let (mime, body) = if cut {
+            ("x-special/gnome-copied-files", format!("cut\n{}", uris.join("\n")))
+        } else {
+            ("text/uri-list", format!("{}\r\n", uris.join("\r\n")))
+        };
+
+        let (program, args): (&str, Vec<&str>) = if is_wayland() {
+            ("wl-copy", vec!["--type", mime])
+        } else {
+            ("xclip", vec!["-selection", "clipboard", "-i", "-t", mime])
+        };
+        // Both tools fork a background owner for the selection, so their
+        // stdout must not be a pipe we'd wait on.
+        let mut child = Command::new(program)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    PlatformError::Unsupported(format!("'{program}' is not installed"))
+                } else {
+                    PlatformError::Io(e)
+                }
+            })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(body.as_bytes())?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(PlatformError::Msg(format!("{program} failed with {status}")));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (paths, cut);
+        Err(PlatformError::Unsupported("clipboard file lists are only implemented on Linux".into()))
+    }
+}
+
+/// Firewall
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FirewallStatus {
+    /// "firewalld" | "ufw" | "nftables" | "none"
+    pub backend: String,
+    pub active: bool,
+}
+
+/// The installed firewall front-end, preferring firewalld, then ufw. The
+/// `nft` tool ships almost everywhere, so plain nftables only counts when
+/// `nftables.service` is enabled to load a ruleset.
+#[cfg(target_os = "linux")]
+fn firewall_backend() -> Option<&'static str> {
+    let installed = |tool: &str| find_in_path(tool).is_some() || std::path::Path::new("/usr/sbin").join(tool).is_file();
+    if installed("firewall-cmd") {
+        Some("firewalld")
+    } else if installed("ufw") {
+        Some("ufw")
+    } else if installed("nft") && unit_is_enabled("nftables") {
+        Some("nftables")
+    } else {
+        None
+    }
+}
+
+/// Whether a systemd unit is active. `systemctl is-active` needs no
+/// privilege, unlike `ufw status` or `nft list`.
+#[cfg(target_os = "linux")]
+fn unit_is_active(unit: &str) -> bool {
+    Command::new("systemctl")
+        .args(["is-active", "--quiet", unit])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn unit_is_enabled(unit: &str) -> bool {
+    Command::new("systemctl")
+        .args(["is-enabled", "--quiet", unit])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// ufw's own switch: `ENABLED=yes` in `/etc/ufw/ufw.conf`. Its systemd unit
+/// is a oneshot that stays "active (exited)" even when ufw is disabled.
+#[cfg(target_os = "linux")]
+fn ufw_enabled() -> bool {
+    std::fs::read_to_string("/etc/ufw/ufw.conf")
+        .map(|conf| {
+            conf.lines()
+                .filter_map(|l| l.trim().strip_prefix("ENABLED="))
+                .any(|v| v.trim_matches(|c| c == '"' || c == '\'').eq_ignore_ascii_case("yes"))
+        })
+        .unwrap_or(false)
+}
+
+pub fn get_firewall_status() -> Result<FirewallStatus, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(match firewall_backend() {
+            Some("ufw") => FirewallStatus { backend: "ufw".into(), active: ufw_enabled() },
+            Some(backend) => FirewallStatus { backend: backend.into(), active: unit_is_active(backend) },
+            None => FirewallStatus { backend: "none".into(), active: false },
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("firewall status is only available on Linux".into()))
+    }
+}
+
+/// Start (and enable at boot) or stop the firewall. Needs root or a polkit
+/// grant.
+#[cfg(feature = "privileged")]
+pub fn set_firewall_enabled(enabled: bool) -> Result<(), PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        // A disabled nftables.service can still be turned on here.
+        let backend = firewall_backend()
+            .or_else(|| find_in_path("nft").is_some().then_some("nftables"))
+            .ok_or_else(|| PlatformError::Unsupported("no firewall (firewalld, ufw, nftables) is installed".into()))?;
+        let result = match (backend, enabled) {
+            ("ufw", true) => run_tool("ufw", &["--force", "enable"]),
+            ("ufw", false) => run_tool("ufw", &["disable"]),
+            (unit, true) => run_tool("systemctl", &["enable", "--now", unit]),
+            (unit, false) => run_tool("systemctl", &["disable", "--now", unit]),
+        };
+        result.map(|_| ()).map_err(|e| match e {
+            PlatformError::Msg(msg)
+                if msg.contains("Access denied")
+                    || msg.contains("authentication")
+                    || msg.contains("need to be root")
+                    || msg.contains("Permission denied") =>
+            {
+                PlatformError::PermissionDenied("changing the firewall state requires root".into())
+            }
+            other => other,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = enabled;
+        Err(PlatformError::Unsupported("firewall control is only available on Linux".into()))
+    }
+}
+
+/// File batches
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct FsOp {
+    /// "copy" | "move" | "delete" | "mkdir"
+    pub kind: String,
+    /// Source for copy/move/delete
+    pub src: Option<String>,
+    /// Destination for copy/move, the directory for mkdir
+    pub dest: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FsBatchResult {
+    /// Indices of ops that completed (before any rollback)
+    pub completed: Vec<usize>,
+    /// Index of the op that failed, if any
+    pub failed_index: Option<usize>,
+    pub error: Option<String>,
+    /// Indices of completed ops that were undone
+    pub rolled_back: Vec<usize>,
+    /// Every completed op was undone (false if a delete had already run
+    /// or an undo step failed)
+    pub rollback_clean: bool,
+}
+
+/// How to undo a completed op.
+enum FsUndo {
+    Remove(PathBuf),
+    MoveBack { from: PathBuf, to: PathBuf },
+    Irreversible,
+}
+
+/// Copy a file, or a directory tree recursively. Symlinks are recreated,
+/// not followed.
+fn copy_tree(src: &std::path::Path, dest: &std::path::Path) -> std::io::Result<()> {
+    let meta = std::fs::symlink_metadata(src)?;
+    if meta.is_dir() {
+        std::fs::create_dir(dest)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else if meta.file_type().is_symlink() {
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::fs::read_link(src)?, dest)
+        }
+        #[cfg(not(unix))]
+        {
+            std::fs::copy(src, dest).map(|_| ())
+        }
+    } else {
+        std::fs::copy(src, dest).map(|_| ())
+    }
+}
+
+fn remove_tree(path: &std::path::Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(path)?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Move by rename, falling back to copy + delete across filesystems.
+fn move_path(src: &std::path::Path, dest: &std::path::Path) -> std::io::Result<()> {
+    match std::fs::rename(src, dest) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices || e.raw_os_error() == Some(18) => {
+            copy_tree(src, dest)?;
+            remove_tree(src)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn apply_fs_op(op: &FsOp) -> Result<FsUndo, PlatformError> {
+    let need = |field: &Option<String>, name: &str| {
+        field
+            .as_deref()
+            .map(PathBuf::from)
+            .ok_or_else(|| PlatformError::Msg(format!("'{}' needs `{name}`", op.kind)))
+    };
+    // Never overwrite: that couldn't be undone.
+    let fresh = |dest: &PathBuf| {
+        if std::fs::symlink_metadata(dest).is_ok() {
+            Err(PlatformError::Msg(format!("'{}' already exists", dest.display())))
+        } else {
+            Ok(())
+        }
+    };
+
+    match op.kind.as_str() {
+        "copy" => {
+            let (src, dest) = (need(&op.src, "src")?, need(&op.dest, "dest")?);
+            fresh(&dest)?;
+            if let Err(e) = copy_tree(&src, &dest) {
+                let _ = remove_tree(&dest);
+                return Err(e.into());
+            }
+            Ok(FsUndo::Remove(dest))
+        }
+        "move" => {
+            let (src, dest) = (need(&op.src, "src")?, need(&op.dest, "dest")?);
+            fresh(&dest)?;
+            move_path(&src, &dest)?;
+            Ok(FsUndo::MoveBack { from: dest, to: src })
+        }
+        "delete" => {
+            remove_tree(&need(&op.src, "src")?)?;
+            Ok(FsUndo::Irreversible)
+        }
+        "mkdir" => {
+            let dest = need(&op.dest, "dest")?;
+            // Undo removes the outermost directory this op created.
+            let mut created = dest.clone();
+            while let Some(parent) = created.parent() {
+                if parent.as_os_str().is_empty() || parent.exists() {
+                    break;
+                }
+                created = parent.to_path_buf();
+            }
+            if dest.is_dir() {
+                return Ok(FsUndo::Irreversible);
+            }
+            std::fs::create_dir_all(&dest)?;
+            Ok(FsUndo::Remove(created))
+        }
+        other => Err(PlatformError::Msg(format!("Unknown op kind '{other}'"))),
+    }
+}
+
+/// Run file operations in order. When one fails, the completed ones are
+/// undone in reverse: copies and created directories are removed, moves are
+/// moved back. Deletes can't be undone (send files to the trash instead if
+/// that matters), so a failure after a delete leaves `rollback_clean` false.
+/// Copies and moves refuse to overwrite an existing destination.
+pub fn fs_batch(ops: &[FsOp]) -> FsBatchResult {
+    let mut undo: Vec<(usize, FsUndo)> = Vec::new();
+    for (index, op) in ops.iter().enumerate() {
+        match apply_fs_op(op) {
+            Ok(step) => undo.push((index, step)),
+            Err(e) => {
+                let completed = undo.iter().map(|(i, _)| *i).collect();
+                let mut rolled_back = Vec::new();
+                let mut rollback_clean = true;
+                for (i, step) in undo.into_iter().rev() {
+                    let undone = match step {
+                        FsUndo::Remove(path) => remove_tree(&path).is_ok(),
+                        FsUndo::MoveBack { from, to } => move_path(&from, &to).is_ok(),
+                        // An op that changed nothing needs no undo.
+                        FsUndo::Irreversible => ops[i].kind == "mkdir",
+                    };
+                    if undone {
+                        rolled_back.push(i);
+                    } else {
+                        rollback_clean = false;
+                    }
+                }
+                return FsBatchResult {
+                    completed,
+                    failed_index: Some(index),
+                    error: Some(e.to_string()),
+                    rolled_back,
+                    rollback_clean,
+                };
+            }
+        }
+    }
+    FsBatchResult {
+        completed: undo.iter().map(|(i, _)| *i).collect(),
+        failed_index: None,
+        error: None,
+        rolled_back: Vec::new(),
+        rollback_clean: true,
+    }
+}
+
+/// Audio activity
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AudioApp {
+    pub name: String,
+    /// "playback" | "recording"
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct AudioActivity {
+    pub playing: bool,
+    pub recording: bool,
+    pub apps: Vec<AudioApp>,
+}
+
+/// Application names of the uncorked streams in `pactl list <what>` output
+/// (one block per stream, properties indented under `Properties:`).
+fn pactl_active_streams(what: &str) -> Option<Vec<String>> {
+    let output = Command::new("pactl")
+        .args(["list", what])
+        .env("LC_ALL", "C")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    let mut names = Vec::new();
+    let mut flush = |name: &mut Option<String>, corked: &mut bool| {
+        if let Some(name) = name.take() {
+            if !*corked && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        *corked = false;
+    };
+    let (mut name, mut corked, mut in_block) = (None, false, false);
+    for line in text.lines() {
+        if !line.starts_with(char::is_whitespace) && !line.is_empty() {
+            flush(&mut name, &mut corked);
+            in_block = true;
+            name = Some("Unknown".to_string());
+            continue;
+        }
+        if !in_block {
+            continue;
+        }
+        let line = line.trim();
+        if let Some(v) = line.strip_prefix("Corked:") {
+            corked = v.trim() == "yes";
+        } else if let Some(v) = line.strip_prefix("application.name = ") {
+            name = Some(v.trim_matches('"').to_string());
+        }
+    }
+    flush(&mut name, &mut corked);
+    Some(names)
+}
+
+/// What is currently playing or recording, from PulseAudio / PipeWire
+/// (via `pactl`) sink-inputs and source-outputs. Paused (corked) streams
+/// don't count. Everything is reported idle when there is no sound server or
+/// `pactl` is missing.
+pub fn get_audio_activity() -> AudioActivity {
+    let playback = pactl_active_streams("sink-inputs").unwrap_or_default();
+    let recording = pactl_active_streams("source-outputs").unwrap_or_default();
+
+    let mut activity = AudioActivity {
+        playing: !playback.is_empty(),
+        recording: !recording.is_empty(),
+        apps: Vec::new(),
+    };
+    for (names, kind) in [(playback, "playback"), (recording, "recording")] {
+        activity.apps.extend(names.into_iter().map(|name| AudioApp {
+            name,
+            kind: kind.to_string(),
+        }));
+    }
+    activity
+}
+
+/// Editors
+
+/// Known terminal emulators: (executable, arguments that precede the command
+/// to run in it).
+const TERMINALS: &[(&str, &[&str])] = &[
+    ("x-terminal-emulator", &["-e"]),
+    ("gnome-terminal", &["--"]),
+    ("ptyxis", &["--"]),
+    ("kgx", &["-e"]),
+    ("konsole", &["-e"]),
+    ("xfce4-terminal", &["-x"]),
+    ("mate-terminal", &["-x"]),
+    ("lxterminal", &["-e"]),
+    ("terminator", &["-x"]),
+    ("kitty", &[]),
+    ("alacritty", &["-e"]),
+    ("foot", &[]),
+    ("wezterm", &["start", "--"]),
+    ("urxvt", &["-e"]),
+    ("xterm", &["-e"]),
+];
+
+/// The terminal used to run console programs: `$TERMINAL` if set, else the
+/// first installed entry of `TERMINALS`. Returns (executable, prefix args).
+fn default_terminal() -> Option<(String, Vec<String>)> {
+    if let Ok(term) = std::env::var("TERMINAL") {
+        let name = term.trim();
+        if !name.is_empty() && find_in_path(name).is_some() {
+            let args = TERMINALS
+                .iter()
+                .find(|(exe, _)| *exe == name)
+                .map_or(&["-e"][..], |(_, args)| *args);
+            return Some((name.to_string(), args.iter().map(|a| a.to_string()).collect()));
+        }
+    }
+    TERMINALS.iter().find(|(exe, _)| find_in_path(exe).is_some()).map(|(exe, args)| {
+        (exe.to_string(), args.iter().map(|a| a.to_string()).collect())
+    })
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TerminalInfo {
+    /// Executable name, e.g. "gnome-terminal"
+    pub name: String,
+    pub path: String,
+    /// How a command is run in it, with `{command}` as the placeholder,
+    /// e.g. "gnome-terminal -- {command}"
+    pub exec_template: String,
+}
+
+fn terminal_info(name: &str, path: PathBuf, prefix: &[String]) -> TerminalInfo {
+    let mut template = vec![name.to_string()];
+    template.extend(prefix.iter().cloned());
+    template.push("{command}".to_string());
+    TerminalInfo { name: name.to_string(), path: path.display().to_string(), exec_template: template.join(" ") }
+}
+
+/// Installed terminal emulators: the known ones found on `$PATH`, plus any
+/// other registered `x-terminal-emulator` alternative on Debian-based
+/// systems (assumed to take `-e`). Empty when none are found.
+pub fn list_terminals() -> Vec<TerminalInfo> {
+    let mut found: Vec<TerminalInfo> = TERMINALS
+        .iter()
+        .filter_map(|(name, prefix)| {
+            let path = find_in_path(name)?;
+            let prefix: Vec<String> = prefix.iter().map(|a| a.to_string()).collect();
+            Some(terminal_info(name, path, &prefix))
+        })
+        .collect();
+
+    #[cfg(target_os = "linux")]
+    if let Ok(alternatives) = run_tool("update-alternatives", &["--list", "x-terminal-emulator"]) {
+        for path in alternatives.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let path = PathBuf::from(path);
+            let Some(name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else { continue };
+            // Debian installs some as wrappers, e.g. gnome-terminal.wrapper.
+            let base = name.split('.').next().unwrap_or(&name).to_string();
+            if !path.is_file() || found.iter().any(|t| t.name == base) {
+                continue;
+            }
+            found.push(terminal_info(&name, path, &["-e".to_string()]));
+        }
+    }
+    found
+}
+
+/// The terminal `open_in_editor` and friends would use; `None` when no
+/// terminal is installed.
+pub fn get_default_terminal() -> Option<TerminalInfo> {
+    let (name, prefix) = default_terminal()?;
+    let path = find_in_path(&name)?;
+    Some(terminal_info(&name, path, &prefix))
+}
+
+/// Editors that need a terminal to run in.
+const TERMINAL_EDITORS: &[&str] = &[
+    "vi", "vim", "nvim", "nano", "micro", "hx", "helix", "kak", "joe", "ne", "mg", "pico", "jed",
+];
+
+/// Arguments that open `path` at `line` for the given editor, or just the
+/// path when the editor has no known way to jump to a line.
+fn editor_open_args(editor: &str, path: &str, line: Option<u32>) -> Vec<String> {
+    let Some(line) = line else {
+        return vec![path.to_string()];
+    };
+    match editor {
+        "vi" | "vim" | "nvim" | "gvim" | "nano" | "micro" | "emacs" | "emacsclient" | "kak" | "joe"
+        | "ne" | "mg" | "pico" | "jed" | "gedit" | "gnome-text-editor" | "mousepad" | "pluma"
+        | "xed" | "geany" => vec![format!("+{line}"), path.to_string()],
+        "code" | "code-oss" | "codium" | "vscodium" => {
+            vec!["--goto".to_string(), format!("{path}:{line}")]
+        }
+        "subl" | "zed" | "hx" | "helix" => vec![format!("{path}:{line}")],
+        "kate" | "kwrite" => vec!["-l".to_string(), line.to_string(), path.to_string()],
+        _ => vec![path.to_string()],
+    }
+}
+
+/// The program (and its fixed arguments) of the default `text/plain`
+/// handler's desktop entry.
+#[cfg(target_os = "linux")]
+fn default_text_editor() -> Option<Vec<String>> {
+    let desktop_id = run_tool("xdg-mime", &["query", "default", "text/plain"]).ok()?;
+    let entry = std::fs::read_to_string(find_desktop_file(desktop_id.lines().next()?)?).ok()?;
+    let exec = entry
+        .lines()
+        .skip_while(|l| l.trim() != "[Desktop Entry]")
+        .skip(1)
+        .take_while(|l| !l.starts_with('['))
+        .find_map(|l| l.strip_prefix("Exec="))?;
+    let words: Vec<String> = exec
+        .split_whitespace()
+        .filter(|w| !w.starts_with('%'))
+        .map(|w| w.trim_matches('"').to_string())
+        .collect();
+    (!words.is_empty()).then_some(words)
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EditorLaunch {
+    /// Editor executable that was started ("default" when the file was
+    /// handed to the system opener)
+    pub editor: String,
+    /// Terminal it was wrapped in, for console editors
+    pub terminal: Option<String>,
+}
+
+/// Open `path` in the user's editor: `$VISUAL`, then `$EDITOR`, then the
+/// default `text/plain` application. Console editors (vim, nano, …) are
+/// started inside a terminal. `line` is passed on where the editor has a
+/// known syntax for it (`+N`, `--goto file:N`, …) and ignored otherwise.
+pub fn open_in_editor(path: &str, line: Option<u32>) -> Result<EditorLaunch, PlatformError> {
+    if !std::path::Path::new(path).exists() {
+        return Err(PlatformError::Msg(format!("'{path}' does not exist")));
+    }
+
+    let configured = ["VISUAL", "EDITOR"].iter().find_map(|var| {
+        let value = std::env::var(var).ok()?;
+        let words: Vec<String> = value.split_whitespace().map(str::to_string).collect();
+        (!words.is_empty() && find_in_path(&words[0]).is_some()).then_some(words)
+    });
+    #[cfg(target_os = "linux")]
+    let words = configured.or_else(default_text_editor);
+    #[cfg(not(target_os = "linux"))]
+    let words = configured;
+
+    let Some(words) = words else {
+        open_system(path)?;
+        return Ok(EditorLaunch { editor: "default".into(), terminal: None });
+    };
+
+    let program = &words[0];
+    let name = std::path::Path::new(program)
+        .file_name()
+        .map_or(program.clone(), |n| n.to_string_lossy().into_owned());
+    let in_terminal = TERMINAL_EDITORS.contains(&name.as_str())
+        || (name == "emacs" && words.iter().any(|w| w == "-nw" || w == "--no-window-system"));
+
+    let (mut cmd, terminal) = if in_terminal {
+        let (terminal, prefix) = default_terminal().ok_or_else(|| {
+            PlatformError::Unsupported(format!("'{name}' needs a terminal and none is installed"))
+        })?;
+        let mut cmd = Command::new(&terminal);
+        cmd.args(prefix).arg(program);
+        (cmd, Some(terminal))
+    } else {
+        (Command::new(program), None)
+    };
+    cmd.args(&words[1..]).args(editor_open_args(&name, path, line));
+    cmd.spawn()?;
+    Ok(EditorLaunch { editor: name, terminal })
+}
+
+/// Clipboard watching
+
+/// Changes closer together than this are reported once.
+const CLIPBOARD_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(150);
+/// Polling interval when no change notifier is available.
+const CLIPBOARD_POLL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Something that identifies the current clipboard contents: the offered
+/// formats plus the default (text) payload.
+#[cfg(target_os = "linux")]
+fn clipboard_fingerprint() -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    clipboard_available_formats(false).unwrap_or_default().hash(&mut hasher);
+    let text = if is_wayland() {
+        clipboard_tool("wl-paste", &["--no-newline"])
+    } else {
+        clipboard_tool("xclip", &["-selection", "clipboard", "-o"])
+    };
+    text.unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Start a native change notifier that prints a line per clipboard change:
+/// `wl-paste --watch` (needs a compositor with the data-control protocol, so
+/// not GNOME) or `clipnotify` (X11, XFIXES selection events). `None` when
+/// neither is usable.
+#[cfg(target_os = "linux")]
+fn spawn_clipboard_notifier() -> Option<(&'static str, std::process::Child)> {
+    use std::os::unix::process::CommandExt;
+    use std::process::Stdio;
+
+    let (mode, script) = if is_wayland() {
+        find_in_path("wl-paste")?;
+        ("wl-paste", "exec wl-paste --watch echo")
+    } else {
+        find_in_path("clipnotify")?;
+        ("clipnotify", "while clipnotify; do echo; done")
+    };
+    let mut child = Command::new("sh")
+        .args(["-c", script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .ok()?;
+    // An unsupported compositor makes wl-paste exit straight away.
+    std::thread::sleep(std::time::Duration::from_millis(150));
+    if !matches!(child.try_wait(), Ok(None)) {
+        return None;
+    }
+    Some((mode, child))
+}
+
+/// Call `on_change(formats)` from a background thread whenever the
+/// clipboard changes, until `stop` is set; bursts of changes within 150 ms
+/// are reported once. Uses a native notifier where one is available
+/// (`wl-paste --watch`, `clipnotify`) and otherwise polls every 500 ms,
+/// which costs a couple of tool runs per tick and only notices changes to
+/// the offered formats or the text payload. Returns the mechanism in use:
+/// "wl-paste", "clipnotify" or "poll".
+pub fn watch_clipboard(
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    mut on_change: impl FnMut(Vec<String>) + Send + 'static,
+) -> Result<&'static str, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::io::BufRead;
+        use std::sync::atomic::Ordering;
+        use std::sync::mpsc::RecvTimeoutError;
+
+        if let Some((mode, mut child)) = spawn_clipboard_notifier() {
+            let stdout = child.stdout.take().ok_or_else(|| PlatformError::Msg("notifier has no stdout".into()))?;
+            let (tx, rx) = std::sync::mpsc::channel();
+            std::thread::spawn(move || {
+                for _ in std::io::BufReader::new(stdout).lines() {
+                    if tx.send(()).is_err() {
+                        break;
+                    }
+                }
+            });
+            std::thread::spawn(move || {
+                // wl-paste --watch also fires once for the current contents.
+                let mut skip = mode == "wl-paste";
+                while !stop.load(Ordering::Relaxed) {
+                    match rx.recv_timeout(std::time::Duration::from_millis(200)) {
+                        Ok(()) => {
+                            while rx.recv_timeout(CLIPBOARD_DEBOUNCE).is_ok() {}
+                            if !std::mem::take(&mut skip) {
+                                on_change(clipboard_available_formats(false).unwrap_or_default());
+                            }
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                kill_process_tree(&mut child);
+                let _ = child.wait();
+            });
+            return Ok(mode);
+        }
+
+        std::thread::spawn(move || {
+            let mut last = clipboard_fingerprint();
+            while !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(CLIPBOARD_POLL);
+                let current = clipboard_fingerprint();
+                if current != last {
+                    std::thread::sleep(CLIPBOARD_DEBOUNCE);
+                    last = clipboard_fingerprint();
+                    on_change(clipboard_available_formats(false).unwrap_or_default());
+                }
+            }
+        });
+        Ok("poll")
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (stop, &mut on_change);
+        Err(PlatformError::Unsupported("clipboard watching is only implemented on Linux".into()))
+    }
+}
+
+/// CPU dashboard
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CoreStats {
+    /// CPU number (the `N` of `cpuN`); offline CPUs are left out
+    pub cpu: usize,
+    pub usage_percent: Option<f64>,
+    pub freq_khz: Option<u64>,
+    pub temp_celsius: Option<f64>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CpuDashboard {
+    pub per_core: Vec<CoreStats>,
+    pub aggregate_percent: Option<f64>,
+    pub load_avg: Option<[f64; 3]>,
+}
+
+/// Jiffy counters from `/proc/stat`: (busy, total) for the aggregate line,
+/// and per online CPU keyed by its number.
+#[cfg(target_os = "linux")]
+type CpuTimes = ((u64, u64), Vec<(usize, (u64, u64))>);
+
+/// Read `/proc/stat`. Offline CPUs have no line, so cores are keyed by the
+/// `N` of their `cpuN` label rather than by position.
+#[cfg(target_os = "linux")]
+fn cpu_times() -> Option<CpuTimes> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    let mut aggregate = None;
+    let mut cores = Vec::new();
+    for line in stat.lines().take_while(|l| l.starts_with("cpu")) {
+        let mut fields = line.split_whitespace();
+        let label = fields.next()?;
+        let values: Vec<u64> = fields.filter_map(|f| f.parse().ok()).collect();
+        // user nice system idle iowait irq softirq steal (guest time is
+        // already included in user/nice)
+        let total: u64 = values.iter().take(8).sum();
+        let idle = values.get(3).copied().unwrap_or(0) + values.get(4).copied().unwrap_or(0);
+        let sample = (total.saturating_sub(idle), total);
+        if label == "cpu" {
+            aggregate = Some(sample);
+        } else if let Some(cpu) = label.strip_prefix("cpu").and_then(|n| n.parse().ok()) {
+            cores.push((cpu, sample));
+        }
+    }
+    Some((aggregate?, cores))
+}
+
+/// Previous `/proc/stat` sample, so frequent callers get usage over the
+/// time since their last call without waiting.
+#[cfg(target_os = "linux")]
+static LAST_CPU_TIMES: std::sync::Mutex<Option<CpuTimes>> = std::sync::Mutex::new(None);
+
+#[cfg(target_os = "linux")]
+fn busy_percent(before: (u64, u64), after: (u64, u64)) -> Option<f64> {
+    let total = after.1.checked_sub(before.1)?;
+    let busy = after.0.checked_sub(before.0)?;
+    (total > 0).then(|| (busy as f64 / total as f64 * 100.0).min(100.0))
+}
+
+/// Core temperatures from `coretemp` hwmon ("Core N" labels, keyed by the
+/// physical core id), plus a package-level reading (coretemp "Package id",
+/// k10temp "Tctl"/"Tdie", …) for CPUs without per-core sensors.
+#[cfg(target_os = "linux")]
+fn cpu_temperatures() -> (std::collections::HashMap<u32, f64>, Option<f64>) {
+    let mut cores = std::collections::HashMap::new();
+    let mut package = None;
+    for hwmon in std::fs::read_dir("/sys/class/hwmon").into_iter().flatten().flatten() {
+        let dir = hwmon.path();
+        let name = std::fs::read_to_string(dir.join("name")).unwrap_or_default();
+        if !matches!(name.trim(), "coretemp" | "k10temp" | "zenpower" | "cpu_thermal") {
+            continue;
+        }
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let file = entry.file_name().to_string_lossy().into_owned();
+            let Some(prefix) = file.strip_suffix("_input").filter(|p| p.starts_with("temp")) else {
+                continue;
+            };
+            let Some(celsius) = std::fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .map(|milli| milli / 1000.0)
+            else {
+                continue;
+            };
+            let label = std::fs::read_to_string(dir.join(format!("{prefix}_label"))).unwrap_or_default();
+            let label = label.trim();
+            if let Some(id) = label.strip_prefix("Core ").and_then(|n| n.parse().ok()) {
+                cores.insert(id, celsius);
+            } else if package.is_none()
+                && (label.is_empty() || label.starts_with("Package") || matches!(label, "Tctl" | "Tdie"))
+            {
+                package = Some(celsius);
+            }
+        }
+    }
+    (cores, package)
+}
+
+/// Per-core usage, frequency and temperature plus the aggregate usage and
+/// load average, read in one pass over `/proc/stat`, cpufreq, hwmon and
+/// `/proc/loadavg`. Usage is measured since the previous call (or over
+/// 100 ms on the first). Any metric that can't be read is `None` rather
+/// than an error.
+pub fn get_cpu_dashboard() -> Result<CpuDashboard, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let stored = LAST_CPU_TIMES.lock().map_err(|e| PlatformError::Msg(e.to_string()))?.take();
+        // Sample twice on the first call, without holding the lock while
+        // sleeping so concurrent callers aren't serialized.
+        let previous = stored.or_else(|| {
+            let first = cpu_times();
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            first
+        });
+        let current = cpu_times();
+        *LAST_CPU_TIMES.lock().map_err(|e| PlatformError::Msg(e.to_string()))? = current.clone();
+
+        let aggregate_percent = match (&previous, &current) {
+            (Some((before, _)), Some((after, _))) => busy_percent(*before, *after),
+            _ => None,
+        };
+
+        let freqs: std::collections::HashMap<usize, u64> = cpufreq_dirs()
+            .into_iter()
+            .filter_map(|(cpu, dir)| {
+                let khz = std::fs::read_to_string(dir.join("scaling_cur_freq")).ok()?;
+                Some((cpu, khz.trim().parse().ok()?))
+            })
+            .collect();
+        let (core_temps, package_temp) = cpu_temperatures();
+
+        let before: std::collections::HashMap<usize, (u64, u64)> =
+            previous.map(|(_, cores)| cores.into_iter().collect()).unwrap_or_default();
+        let per_core = current
+            .map(|(_, cores)| cores)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(cpu, after)| {
+                let usage_percent = before.get(&cpu).and_then(|b| busy_percent(*b, after));
+                let core_id = std::fs::read_to_string(format!(
+                    "/sys/devices/system/cpu/cpu{cpu}/topology/core_id"
+                ))
+                .ok()
+                .and_then(|id| id.trim().parse::<u32>().ok());
+                let temp_celsius = core_id.and_then(|id| core_temps.get(&id).copied()).or(package_temp);
+                CoreStats { cpu, usage_percent, freq_khz: freqs.get(&cpu).copied(), temp_celsius }
+            })
+            .collect();
+
+        let load_avg = std::fs::read_to_string("/proc/loadavg").ok().and_then(|raw| {
+            let values: Vec<f64> = raw.split_whitespace().take(3).filter_map(|v| v.parse().ok()).collect();
+            Some([*values.first()?, *values.get(1)?, *values.get(2)?])
+        });
+
+        Ok(CpuDashboard { per_core, aggregate_percent, load_avg })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(PlatformError::Unsupported("the CPU dashboard is only available on Linux".into()))
+    }
+}
+
+/// Command audit
+
+/// Environment variable name patterns redacted from the audit log unless
+/// configured otherwise (`*` matches anything, case-insensitive).
+pub const DEFAULT_REDACT_PATTERNS: &[&str] =
+    &["*TOKEN*", "*SECRET*", "*PASSWORD*", "*PASSWD*", "*API_KEY*", "*CREDENTIAL*", "*PRIVATE_KEY*"];
+
+#[derive(Debug, Clone)]
+pub struct CommandAudit {
+    /// JSON Lines file every shell command is appended to
+    pub path: PathBuf,
+    /// Variables whose names match one of these are logged as "***"
+    pub redact: Vec<String>,
+}
+
+impl CommandAudit {
+    /// Log to `path`, redacting `DEFAULT_REDACT_PATTERNS` plus `extra_redact`.
+    pub fn new(path: PathBuf, extra_redact: Vec<String>) -> Self {
+        let mut redact: Vec<String> = DEFAULT_REDACT_PATTERNS.iter().map(|p| p.to_string()).collect();
+        redact.extend(extra_redact);
+        CommandAudit { path, redact }
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        let options = glob::MatchOptions { case_sensitive: false, ..Default::default() };
+        self.redact
+            .iter()
+            .filter_map(|p| glob::Pattern::new(p).ok())
+            .any(|p| p.matches_with(key, options))
+    }
+}
+
+static COMMAND_AUDIT: std::sync::OnceLock<CommandAudit> = std::sync::OnceLock::new();
+/// Hash of the last audit record written, so records chain in order.
+static AUDIT_LAST_HASH: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Turn on the command audit log for the rest of the run. Only the first
+/// call takes effect.
+pub fn set_command_audit(audit: CommandAudit) {
+    let _ = COMMAND_AUDIT.set(audit);
+}
+
+/// The audit log file, when auditing is on.
+pub fn audit_log_path() -> Option<PathBuf> {
+    COMMAND_AUDIT.get().map(|audit| audit.path.clone())
+}
+
+/// Append a record for `command` to the audit log (a no-op when auditing is
+/// off): time, command, working directory and the environment the command
+/// will see, with sensitive values replaced by "***". Each record carries
+/// the SHA-256 of the previous line as `prev_hash`, so edits or deletions
+/// in the middle of the file break the chain. Failing to write refuses the
+/// command rather than running it unrecorded.
+fn audit_command(command: &str, opts: &ShellOptions) -> Result<(), PlatformError> {
+    match COMMAND_AUDIT.get() {
+        Some(audit) => write_audit_record(audit, &AUDIT_LAST_HASH, command, opts),
+        None => Ok(()),
+    }
+}
+
+/// Append one record to `audit`'s log, chaining it to `last_hash`.
+fn write_audit_record(
+    audit: &CommandAudit,
+    last_hash: &std::sync::Mutex<Option<String>>,
+    command: &str,
+    opts: &ShellOptions,
+) -> Result<(), PlatformError> {
+    use std::io::Write;
+
+    let mut env: std::collections::BTreeMap<String, String> = std::env::vars().collect();
+    env.extend(opts.env.iter().cloned());
+    for (key, value) in env.iter_mut() {
+        if audit.is_sensitive(key) {
+            *value = "***".to_string();
+        }
+    }
+    let cwd = match &opts.cwd {
+        Some(dir) => dir.clone(),
+        None => std::env::current_dir()?,
+    };
+    let time_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    let mut last_hash = last_hash.lock().map_err(|e| PlatformError::Msg(e.to_string()))?;
+    let prev_hash = match last_hash.take() {
+        Some(hash) => hash,
+        // Continue the chain of an existing log.
+        None => std::fs::read_to_string(&audit.path)
+            .ok()
+            .and_then(|log| log.lines().rev().find(|l| !l.trim().is_empty()).map(|l| sha256_hex(l.as_bytes())))
+            .unwrap_or_else(|| "0".repeat(64)),
+    };
+    let line = serde_json::json!({
+        "time_ms": time_ms,
+        "command": command,
+        "cwd": cwd.display().to_string(),
+        "env": env,
+        "prev_hash": prev_hash,
+    })
+    .to_string();
+
+    let mut file = open_log_file(&audit.path)?;
+    let written = writeln!(file, "{line}");
+    // Keep the old hash on failure so the chain stays intact.
+    *last_hash = Some(if written.is_ok() { sha256_hex(line.as_bytes()) } else { prev_hash });
+    written.map_err(|e| PlatformError::Msg(format!("Failed to write the command audit log: {e}")))
+}
+
+/// Window feature support
+
+/// Whether a window feature is expected to work in this session: "yes",
+/// "no" or "unknown". Based on the display server, the desktop, and on X11
+/// the window manager's `_NET_SUPPORTED` list (see `get_wm_capabilities`).
+/// Features: "transparency", "always_on_top", "decorations", "shape",
+/// "click_through", "sticky", "progress", "badge".
+pub fn window_feature_supported(feature: &str) -> Result<&'static str, PlatformError> {
+    #[cfg(target_os = "linux")]
+    {
+        let wayland = is_wayland();
+        // The WM's EWMH atoms; `None` when it doesn't advertise any.
+        let net_supported = || -> Option<Vec<String>> {
+            let caps = get_wm_capabilities().ok()?;
+            (!caps.supported.is_empty()).then_some(caps.supported)
+        };
+        let ewmh_state = |atom: &str| match net_supported() {
+            Some(atoms) if atoms.iter().any(|a| a == atom) => "yes",
+            Some(_) => "no",
+            None => "unknown",
+        };
+
+        Ok(match feature {
+            // Wayland is always composited; on X11 it depends on a
+            // compositing manager, which GNOME and KDE always run.
+            "transparency" if wayland => "yes",
+            "transparency" if desktop_is("gnome") || desktop_is("kde") => "yes",
+            "transparency" => "unknown",
+            // No Wayland protocol lets clients keep themselves above others.
+            "always_on_top" if wayland => "no",
+            "always_on_top" => ewmh_state("_NET_WM_STATE_ABOVE"),
+            "decorations" => "yes",
+            // Wayland only gets an input region, not a clipped surface.
+            "shape" if wayland => "no",
+            "shape" => "yes",
+            "click_through" => "yes",
+            "sticky" if wayland => "no",
+            "sticky" => ewmh_state("_NET_WM_STATE_STICKY"),
+            // Unity LauncherEntry: Ubuntu Dock, Plank and KDE honor it;
+            // stock GNOME doesn't unless a dock extension is installed.
+            "progress" | "badge" if desktop_is("ubuntu") || desktop_is("kde") || desktop_is("unity") => "yes",
+            "progress" | "badge" => "unknown",
+            other => return Err(PlatformError::Msg(format!("Unknown window feature '{other}'"))),
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = feature;
+        Err(PlatformError::Unsupported("window feature detection is only implemented on Linux".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_pads_and_strips_prefix() {
+        assert_eq!(parse_version("v1").unwrap(), semver::Version::new(1, 0, 0));
+        assert_eq!(parse_version(" V1.2 ").unwrap(), semver::Version::new(1, 2, 0));
+        assert_eq!(parse_version("1.2.3").unwrap(), semver::Version::new(1, 2, 3));
+    }
+
+    #[test]
+    fn parse_version_keeps_pre_release_and_build() {
+        let v = parse_version("1.2-rc1").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (1, 2, 0));
+        assert_eq!(v.pre.as_str(), "rc1");
+        assert_eq!(parse_version("2+build.5").unwrap().build.as_str(), "build.5");
+    }
+
+    #[test]
+    fn parse_version_rejects_garbage() {
+        assert!(parse_version("").is_err());
+        assert!(parse_version("one.two").is_err());
+        assert!(parse_version("1.2.3.4").is_err());
+    }
+
+    #[test]
+    fn compare_versions_orders_pre_releases_first() {
+        assert_eq!(compare_versions("1.2-rc1", "1.2").unwrap(), -1);
+        assert_eq!(compare_versions("v1", "1.0.0").unwrap(), 0);
+        assert_eq!(compare_versions("1.10", "1.9").unwrap(), 1);
+        assert!(compare_versions("1.0", "nope").is_err());
+    }
+
+    #[test]
+    fn version_satisfies_uses_padded_versions() {
+        assert!(version_satisfies("v1.5", ">=1.2, <2").unwrap());
+        assert!(!version_satisfies("2", ">=1.2, <2").unwrap());
+        assert!(version_satisfies("1", "bad req").is_err());
+    }
+
+    #[test]
+    fn parse_csv_keys_rows_by_header() {
+        let CsvRows::Keyed(rows) = parse_csv("name,age\nada,36\n\"b, c\",7\n", true, ',').unwrap() else {
+            panic!("expected keyed rows");
+        };
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["name"], "ada");
+        assert_eq!(rows[1]["name"], "b, c");
+        assert_eq!(rows[1]["age"], "7");
+    }
+
+    #[test]
+    fn parse_csv_plain_rows_with_custom_delimiter() {
+        let CsvRows::Plain(rows) = parse_csv("a;b\nc;d\n", false, ';').unwrap() else {
+            panic!("expected plain rows");
+        };
+        assert_eq!(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+    }
+
+    #[test]
+    fn parse_csv_rejects_bad_input() {
+        assert!(parse_csv("a,b", false, '§').is_err());
+        assert!(parse_csv("a,b\n1,2,3\n", true, ',').is_err());
+    }
+
+    fn fs_op(kind: &str, src: Option<&std::path::Path>, dest: Option<&std::path::Path>) -> FsOp {
+        FsOp {
+            kind: kind.to_string(),
+            src: src.map(|p| p.to_string_lossy().into_owned()),
+            dest: dest.map(|p| p.to_string_lossy().into_owned()),
+        }
+    }
+
+    #[test]
+    fn fs_batch_rolls_back_completed_ops() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, moved) = (dir.path().join("a.txt"), dir.path().join("moved.txt"));
+        std::fs::write(&file, "hello").unwrap();
+        let new_dir = dir.path().join("new/nested");
+        let copy = dir.path().join("new/nested/a.txt");
+
+        let result = fs_batch(&[
+            fs_op("mkdir", None, Some(&new_dir)),
+            fs_op("copy", Some(&file), Some(&copy)),
+            fs_op("move", Some(&file), Some(&moved)),
+            fs_op("copy", Some(&dir.path().join("missing")), Some(&dir.path().join("b.txt"))),
+        ]);
+
+        assert_eq!(result.completed, vec![0, 1, 2]);
+        assert_eq!(result.failed_index, Some(3));
+        assert!(result.error.is_some());
+        assert_eq!(result.rolled_back, vec![2, 1, 0]);
+        assert!(result.rollback_clean);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "hello");
+        assert!(!moved.exists());
+        assert!(!dir.path().join("new").exists());
+    }
+
+    #[test]
+    fn fs_batch_refuses_overwrite_and_flags_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+        std::fs::write(&a, "a").unwrap();
+        std::fs::write(&b, "b").unwrap();
+
+        let result = fs_batch(&[fs_op("delete", Some(&a), None), fs_op("copy", Some(&b), Some(&b))]);
+
+        assert_eq!(result.failed_index, Some(1));
+        assert!(result.rolled_back.is_empty());
+        assert!(!result.rollback_clean);
+        assert!(!a.exists());
+        assert_eq!(std::fs::read_to_string(&b).unwrap(), "b");
+    }
+
+    // Uses a local audit config, leaving the process-wide log off for other tests.
+    #[test]
+    fn audit_records_chain_and_redact() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let audit = CommandAudit::new(path.clone(), vec!["my_*".to_string()]);
+        let last_hash = std::sync::Mutex::new(None);
+
+        let opts = ShellOptions {
+            cwd: Some(dir.path().to_path_buf()),
+            env: vec![
+                ("SERVICE_TOKEN".to_string(), "hunter2".to_string()),
+                ("MY_VAR".to_string(), "private".to_string()),
+                ("PLAIN".to_string(), "visible".to_string()),
+            ],
+            ..Default::default()
+        };
+        write_audit_record(&audit, &last_hash, "echo one", &opts).unwrap();
+        write_audit_record(&audit, &last_hash, "echo two", &opts).unwrap();
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let records: Vec<serde_json::Value> = lines.iter().map(|l| serde_json::from_str(l).unwrap()).collect();
+
+        assert_eq!(records[0]["command"], "echo one");
+        assert_eq!(records[0]["prev_hash"], "0".repeat(64));
+        assert_eq!(records[1]["prev_hash"], sha256_hex(lines[0].as_bytes()));
+        assert_eq!(records[1]["cwd"], dir.path().display().to_string());
+        assert_eq!(records[1]["env"]["SERVICE_TOKEN"], "***");
+        assert_eq!(records[1]["env"]["MY_VAR"], "***");
+        assert_eq!(records[1]["env"]["PLAIN"], "visible");
+    }
 }
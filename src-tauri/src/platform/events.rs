@@ -0,0 +1,248 @@
+//! Central event emission with a per-channel rate limit, so streaming
+//! features (command output, watchers, monitors) can't flood the webview.
+//!
+//! Everything that emits to the frontend should go through [`emit`] (or
+//! [`emit_per_id`] when several producers share a channel). Lifecycle and
+//! data events the frontend can't recover from losing (`cmd://exit`, file
+//! chunks, …) use [`emit_unlimited`] instead.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// Channel on which drop summaries are reported.
+pub const DROPPED_CHANNEL: &str = "events://dropped";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DroppedSummary {
+    pub channel: String,
+    /// Stream/watch id the window belonged to, for [`emit_per_id`] events
+    pub id: Option<u64>,
+    pub dropped: u64,
+}
+
+/// A rate-limit window: the channel plus, for [`emit_per_id`], the id.
+type WindowKey = (String, Option<u64>);
+
+struct ChannelWindow {
+    started: Instant,
+    sent: u32,
+    dropped: u64,
+}
+
+/// Managed state holding the limit and the current one-second window per channel.
+#[derive(Default)]
+pub struct EventLimiter {
+    limit: Mutex<Option<u32>>,
+    channels: Mutex<HashMap<WindowKey, ChannelWindow>>,
+    /// A flush thread is pending; only changed under the `channels` lock.
+    flush_scheduled: AtomicBool,
+}
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Summaries for every channel whose window has closed with drops, resetting
+/// those windows. Also returns how long until the next still-open window
+/// with drops closes, if any.
+fn take_expired(
+    channels: &mut HashMap<WindowKey, ChannelWindow>,
+    now: Instant,
+) -> (Vec<DroppedSummary>, Option<Duration>) {
+    let mut summaries = Vec::new();
+    let mut next: Option<Duration> = None;
+    for ((channel, id), window) in channels.iter_mut() {
+        let age = now.duration_since(window.started);
+        if age >= WINDOW {
+            if window.dropped > 0 {
+                summaries.push(DroppedSummary { channel: channel.clone(), id: *id, dropped: window.dropped });
+            }
+            *window = ChannelWindow { started: now, sent: 0, dropped: 0 };
+        } else if window.dropped > 0 {
+            let left = WINDOW - age;
+            next = Some(next.map_or(left, |n| n.min(left)));
+        }
+    }
+    (summaries, next)
+}
+
+impl EventLimiter {
+    /// `None` (or 0) disables limiting.
+    pub fn set_limit(&self, events_per_sec: Option<u32>) {
+        if let Ok(mut limit) = self.limit.lock() {
+            *limit = events_per_sec.filter(|n| *n > 0);
+        }
+    }
+
+    /// Decide whether an event on `channel` (from producer `id`, if keyed)
+    /// may go out now. Also returns
+    /// the drop summaries of any channel whose window has closed, and, when
+    /// this event starts a flush being needed, how long to wait before
+    /// flushing (so a burst that simply stops still gets reported).
+    fn admit(&self, channel: &str, id: Option<u64>) -> (bool, Vec<DroppedSummary>, Option<Duration>) {
+        let Some(limit) = self.limit.lock().ok().and_then(|l| *l) else {
+            return (true, Vec::new(), None);
+        };
+        let Ok(mut channels) = self.channels.lock() else { return (true, Vec::new(), None) };
+        let now = Instant::now();
+        let (summaries, _) = take_expired(&mut channels, now);
+        let window = channels.entry((channel.to_string(), id)).or_insert(ChannelWindow {
+            started: now,
+            sent: 0,
+            dropped: 0,
+        });
+
+        if window.sent < limit {
+            window.sent += 1;
+            return (true, summaries, None);
+        }
+        window.dropped += 1;
+        let flush_in = WINDOW.saturating_sub(now.duration_since(window.started));
+        let schedule = !self.flush_scheduled.swap(true, Ordering::Relaxed);
+        (false, summaries, schedule.then_some(flush_in))
+    }
+
+    /// Report windows that closed with drops. Returns the delay to the next
+    /// flush, or `None` (clearing the schedule) when nothing is pending.
+    fn flush(&self) -> (Vec<DroppedSummary>, Option<Duration>) {
+        let Ok(mut channels) = self.channels.lock() else {
+            self.flush_scheduled.store(false, Ordering::Relaxed);
+            return (Vec::new(), None);
+        };
+        let (summaries, next) = take_expired(&mut channels, Instant::now());
+        if next.is_none() {
+            self.flush_scheduled.store(false, Ordering::Relaxed);
+        }
+        (summaries, next)
+    }
+}
+
+/// Emit `payload` on `channel` to all webviews, subject to the rate limit.
+/// Excess events within a one-second window are dropped and reported on
+/// [`DROPPED_CHANNEL`] once the window has passed, from a timer if no
+/// further event comes along.
+pub fn emit<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, channel: &str, payload: S) {
+    emit_limited(app, channel, None, payload);
+}
+
+/// [`emit`], with a separate rate-limit window per `id`, so one busy
+/// producer on a shared channel (e.g. one of several `cmd://stdout`
+/// streams) can't starve the others.
+pub fn emit_per_id<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, channel: &str, id: u64, payload: S) {
+    emit_limited(app, channel, Some(id), payload);
+}
+
+/// Emit without rate limiting, for events that must never be dropped:
+/// lifecycle notifications and data whose loss would corrupt a transfer.
+pub fn emit_unlimited<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, channel: &str, payload: S) {
+    let _ = app.emit(channel, payload);
+}
+
+fn emit_limited<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, channel: &str, id: Option<u64>, payload: S) {
+    let (admit, summaries, flush_in) = match app.try_state::<EventLimiter>() {
+        Some(limiter) => limiter.admit(channel, id),
+        None => (true, Vec::new(), None),
+    };
+    for summary in summaries {
+        let _ = app.emit(DROPPED_CHANNEL, summary);
+    }
+    if admit {
+        let _ = app.emit(channel, payload);
+    }
+    if let Some(mut delay) = flush_in {
+        let app = app.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(delay);
+            let Some(limiter) = app.try_state::<EventLimiter>() else { return };
+            let (summaries, next) = limiter.flush();
+            for summary in summaries {
+                let _ = app.emit(DROPPED_CHANNEL, summary);
+            }
+            match next {
+                Some(next) => delay = next,
+                None => return,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admit_drops_over_limit_per_id() {
+        let limiter = EventLimiter::default();
+        limiter.set_limit(Some(2));
+        assert!(limiter.admit("cmd://stdout", Some(1)).0);
+        assert!(limiter.admit("cmd://stdout", Some(1)).0);
+
+        let (admitted, summaries, flush_in) = limiter.admit("cmd://stdout", Some(1));
+        assert!(!admitted);
+        assert!(summaries.is_empty());
+        assert!(flush_in.is_some_and(|d| d <= WINDOW));
+        // Only the first drop schedules a flush.
+        assert_eq!(limiter.admit("cmd://stdout", Some(1)).2, None);
+
+        // Another id on the same channel has its own window.
+        assert!(limiter.admit("cmd://stdout", Some(2)).0);
+    }
+
+    #[test]
+    fn flush_reports_closed_windows_with_drops() {
+        let limiter = EventLimiter::default();
+        limiter.set_limit(Some(1));
+        limiter.admit("a", None);
+        limiter.admit("a", None);
+        limiter.admit("a", None);
+        limiter.admit("b", Some(7));
+
+        // Window still open: nothing to report yet, flush again later.
+        let (summaries, next) = limiter.flush();
+        assert!(summaries.is_empty());
+        assert!(next.is_some());
+
+        for window in limiter.channels.lock().unwrap().values_mut() {
+            window.started -= WINDOW;
+        }
+        let (summaries, next) = limiter.flush();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].channel, "a");
+        assert_eq!(summaries[0].id, None);
+        assert_eq!(summaries[0].dropped, 2);
+        assert_eq!(next, None);
+        assert!(!limiter.flush_scheduled.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn take_expired_resets_only_closed_windows() {
+        let now = Instant::now();
+        let mut channels = HashMap::new();
+        channels.insert(("old".to_string(), None), ChannelWindow { started: now - WINDOW, sent: 5, dropped: 3 });
+        channels.insert(
+            ("open".to_string(), Some(1)),
+            ChannelWindow { started: now - WINDOW / 2, sent: 5, dropped: 1 },
+        );
+
+        let (summaries, next) = take_expired(&mut channels, now);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].channel, "old");
+        assert_eq!(summaries[0].dropped, 3);
+        assert_eq!(next, Some(WINDOW / 2));
+
+        let old = &channels[&("old".to_string(), None)];
+        assert_eq!((old.sent, old.dropped), (0, 0));
+        assert_eq!(channels[&("open".to_string(), Some(1))].dropped, 1);
+    }
+
+    #[test]
+    fn no_limit_admits_everything() {
+        let limiter = EventLimiter::default();
+        for _ in 0..100 {
+            assert!(limiter.admit("x", None).0);
+        }
+    }
+}